@@ -0,0 +1,416 @@
+//! Contains structures related to the config file
+//!
+//! The config file allows languages to be added at runtime without rebuilding ed_strip.
+//! Each language either points to a compiled tree-sitter grammar (a shared library, such as a .so)
+//! and the symbol that returns the language from it, or describes its comment syntax for the lexer.
+use std::path::{Path, PathBuf};
+
+use libloading::{Library, Symbol};
+use log::debug;
+use serde::Deserialize;
+use thiserror::Error;
+use tree_sitter::{Language, LanguageError, Parser as TSParser};
+use tree_sitter_language::LanguageFn;
+
+use crate::{
+    languages::{find_language, LanguageDefinition, Stripper},
+    strip_lexer::LexerDefinition,
+    type_hints::GlobPattern,
+};
+
+/// A tree-sitter grammar compiled to a shared library
+#[derive(Deserialize)]
+pub struct GrammarConfig {
+    /// The path to the shared library
+    /// Relative paths are resolved against the directory containing the config file
+    pub library: PathBuf,
+
+    /// The symbol that returns the language, e.g. tree_sitter_solidity
+    pub symbol: String,
+
+    /// The list of tree-sitter nodes that are comments
+    pub comment_node_types: Vec<String>,
+}
+
+/// A language declared in the config file
+#[derive(Deserialize)]
+pub struct LanguageConfig {
+    /// The name of the language
+    pub name: String,
+
+    /// A stable identifier for the language. Defaults to the lower case name
+    pub id: Option<String>,
+
+    /// Alternative names for the language
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    /// The file extensions to strip using this language
+    #[serde(default)]
+    pub file_extensions: Vec<String>,
+
+    /// The path globs to strip using this language
+    #[serde(default)]
+    pub path_globs: Vec<GlobPattern>,
+
+    /// The prefixes of comments that are documentation, such as /** for Javadoc
+    #[serde(default)]
+    pub doc_comment_prefixes: Vec<String>,
+
+    /// The stripper used to remove comments
+    #[serde(flatten)]
+    pub stripper: StripperConfig,
+}
+
+/// The stripper used by a language declared in the config file
+/// Exactly one of these must be specified
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripperConfig {
+    /// A tree-sitter grammar compiled to a shared library
+    Grammar(GrammarConfig),
+
+    /// A lexer for languages with simple comment syntax
+    Lexer(LexerDefinition),
+}
+
+/// A type representing a config file
+///
+/// This corresponds to
+///
+/// {
+///   languages?: {
+///     name: string,
+///     id?: string,
+///     aliases?: string[],
+///     file_extensions?: string[],
+///     path_globs?: string[],
+///     doc_comment_prefixes?: string[],
+///     grammar?: {
+///       library: string,
+///       symbol: string,
+///       comment_node_types: string[]
+///     },
+///     lexer?: {
+///       line_comments?: {
+///         start: string,
+///         line_start_only?: boolean,
+///         ignore_case?: boolean,
+///         whole_word?: boolean,
+///         followed_by_whitespace?: boolean,
+///         preceded_by_whitespace?: boolean,
+///         not_followed_by?: string[]
+///       }[],
+///       block_comments?: {
+///         start: string,
+///         end: string,
+///         nested?: boolean,
+///         line_start_only?: boolean,
+///         documentation?: boolean,
+///         ignore_case?: boolean,
+///         not_followed_by?: string[]
+///       }[],
+///       strings?: {
+///         start: string,
+///         end: string,
+///         escape?: string,
+///         multiline?: boolean,
+///         escape_sequences?: string[],
+///         interpolation?: string,
+///         documentation?: boolean
+///       }[],
+///       dollar_quoted_strings?: boolean,
+///       trailing_commas?: boolean,
+///       heredocs?: boolean,
+///       quote_like_operators?: boolean,
+///       end_markers?: string[]
+///     }
+///   }[]
+/// }
+///
+#[derive(Deserialize, Default)]
+pub struct Config {
+    /// Languages to register alongside the built-in languages
+    #[serde(default)]
+    pub languages: Vec<LanguageConfig>,
+}
+
+/// Config load error
+#[derive(Error, Debug)]
+pub enum ConfigLoadError {
+    #[error("unable to load file: {0}")]
+    IOError(#[from] std::io::Error),
+
+    #[error("unable to parse file: {0}")]
+    ParseError(#[from] serde_json::Error),
+
+    #[error("unable to load grammar '{symbol}' from {path}: {source}")]
+    LibraryError {
+        path: PathBuf,
+        symbol: String,
+        source: libloading::Error,
+    },
+
+    // Typically this means the grammar was generated by an incompatible version of tree-sitter
+    #[error("language error: {0}")]
+    LanguageError(#[from] LanguageError),
+
+    #[error("language '{name}' is already defined")]
+    DuplicateLanguage { name: String },
+}
+
+/// Loads the config file
+///
+/// Relative grammar library paths are resolved against the directory containing the config file
+///
+/// # Arguments
+/// * `path` - The path to the config file
+pub fn load_config_file(path: &Path) -> Result<Config, ConfigLoadError> {
+    debug!("Loading config from {}", path.display());
+    let contents = std::fs::read(path)?;
+    let mut config: Config = serde_json::from_slice(&contents)?;
+
+    let config_dir = path.parent().unwrap_or(Path::new(""));
+    for language in config.languages.iter_mut() {
+        if let StripperConfig::Grammar(grammar) = &mut language.stripper {
+            grammar.library = config_dir.join(&grammar.library);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Converts a String to a &'static str
+/// Languages live for the duration of the program, so this is not a meaningful leak
+fn leak(value: &str) -> &'static str {
+    value.to_string().leak()
+}
+
+/// Loads a tree-sitter language from a shared library
+///
+/// # Arguments
+/// * `grammar` - The grammar to load
+///
+/// # Return
+/// On success, the tree-sitter language
+fn load_grammar(grammar: &GrammarConfig) -> Result<Language, ConfigLoadError> {
+    debug!(
+        "Loading grammar {} from {}",
+        grammar.symbol,
+        grammar.library.display()
+    );
+
+    let library_error = |source| ConfigLoadError::LibraryError {
+        path: grammar.library.clone(),
+        symbol: grammar.symbol.clone(),
+        source,
+    };
+
+    // Safety: Loading the library runs its initialisers, and we trust that the symbol has the
+    // signature of a tree-sitter language function. Both are under the control of the user
+    let library = unsafe { Library::new(&grammar.library) }.map_err(library_error)?;
+    let language_fn = unsafe {
+        let symbol: Symbol<unsafe extern "C" fn() -> *const ()> = library
+            .get(grammar.symbol.as_bytes())
+            .map_err(library_error)?;
+        LanguageFn::from_raw(*symbol)
+    };
+
+    // The language refers to static data inside the library, so it must never be unloaded
+    std::mem::forget(library);
+    let language = Language::new(language_fn);
+
+    // Check the grammar is compatible now, rather than failing on every file
+    TSParser::new().set_language(&language)?;
+    Ok(language)
+}
+
+impl Config {
+    /// Loads the grammars of each language in the config file
+    ///
+    /// Names, identifiers and aliases must not clash with existing languages, or with other
+    /// languages in the config file
+    ///
+    /// # Return
+    /// On success, a LanguageDefinition for each language in the config file
+    pub fn language_definitions(&self) -> Result<Vec<LanguageDefinition>, ConfigLoadError> {
+        let mut definitions: Vec<LanguageDefinition> = Vec::new();
+
+        for language in self.languages.iter() {
+            let id = language
+                .id
+                .clone()
+                .unwrap_or_else(|| language.name.to_lowercase());
+
+            // Ensure the language can be unambiguously found
+            let clash = std::iter::once(&language.name)
+                .chain(std::iter::once(&id))
+                .chain(language.aliases.iter())
+                .find(|name| {
+                    find_language(name).is_some()
+                        || definitions.iter().any(|definition| {
+                            definition.name.eq_ignore_ascii_case(name)
+                                || definition.id.eq_ignore_ascii_case(name)
+                                || definition
+                                    .aliases
+                                    .iter()
+                                    .any(|alias| alias.eq_ignore_ascii_case(name))
+                        })
+                });
+            if let Some(clash) = clash {
+                return Err(ConfigLoadError::DuplicateLanguage {
+                    name: clash.clone(),
+                });
+            }
+
+            definitions.push(LanguageDefinition {
+                name: leak(&language.name),
+                id: leak(&id.to_lowercase()),
+                aliases: language
+                    .aliases
+                    .iter()
+                    .map(|alias| leak(&alias.to_lowercase()))
+                    .collect(),
+                file_extensions: language
+                    .file_extensions
+                    .iter()
+                    .map(|extension| leak(&extension.to_lowercase()))
+                    .collect(),
+                path_globs: language
+                    .path_globs
+                    .iter()
+                    .map(|path_glob| (**path_glob).clone())
+                    .collect(),
+                doc_comment_prefixes: language
+                    .doc_comment_prefixes
+                    .iter()
+                    .map(|prefix| leak(prefix))
+                    .collect::<Vec<&'static str>>()
+                    .leak(),
+                stripper: match &language.stripper {
+                    StripperConfig::Grammar(grammar) => Stripper::TreeSitter {
+                        language: load_grammar(grammar)?,
+                        comment_node_types: grammar
+                            .comment_node_types
+                            .iter()
+                            .map(|node_type| leak(node_type))
+                            .collect(),
+                        separator_comment_types: [].into(),
+                        docstrings: None,
+                        injections: vec![],
+                    },
+                    StripperConfig::Lexer(lexer) => Stripper::Lexer(lexer.clone()),
+                },
+            });
+        }
+
+        Ok(definitions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// GIVEN A config file declaring a language
+    /// WHEN it is deserialized
+    /// THEN optional fields are defaulted
+    #[test]
+    fn config_optional_fields_default() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "languages": [{
+                    "name": "Solidity",
+                    "grammar": {
+                        "library": "libtree-sitter-solidity.so",
+                        "symbol": "tree_sitter_solidity",
+                        "comment_node_types": ["comment"]
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.languages.len(), 1);
+        assert!(config.languages[0].id.is_none());
+        assert!(config.languages[0].aliases.is_empty());
+        assert!(config.languages[0].file_extensions.is_empty());
+        assert!(config.languages[0].path_globs.is_empty());
+    }
+
+    /// GIVEN A config file declaring a lexer based language
+    /// WHEN the language definitions are loaded
+    /// THEN the lexer is used to strip the language
+    #[test]
+    fn config_loads_lexer_languages() {
+        let config: Config = serde_json::from_str(
+            r##"{
+                "languages": [{
+                    "name": "Crontab",
+                    "path_globs": ["**/crontab"],
+                    "lexer": {
+                        "line_comments": [{ "start": "#", "line_start_only": true }]
+                    }
+                }]
+            }"##,
+        )
+        .unwrap();
+
+        let definitions = config.language_definitions().unwrap();
+        assert_eq!(definitions[0].id, "crontab");
+        assert!(matches!(
+            &definitions[0].stripper,
+            Stripper::Lexer(LexerDefinition { line_comments, .. }) if line_comments.len() == 1
+        ));
+    }
+
+    /// GIVEN A config file declaring a language with the same name as a built-in language
+    /// WHEN the language definitions are loaded
+    /// THEN an error is returned
+    #[test]
+    fn config_rejects_duplicate_languages() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "languages": [{
+                    "name": "Solidity",
+                    "aliases": ["PY"],
+                    "grammar": {
+                        "library": "libtree-sitter-solidity.so",
+                        "symbol": "tree_sitter_solidity",
+                        "comment_node_types": ["comment"]
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let result = config.language_definitions();
+        assert!(matches!(
+            result,
+            Err(ConfigLoadError::DuplicateLanguage { name }) if name == "PY"
+        ));
+    }
+
+    /// GIVEN A config file declaring a language with a missing grammar library
+    /// WHEN the language definitions are loaded
+    /// THEN an error is returned
+    #[test]
+    fn config_reports_missing_libraries() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "languages": [{
+                    "name": "Solidity",
+                    "grammar": {
+                        "library": "/nonexistent/libtree-sitter-solidity.so",
+                        "symbol": "tree_sitter_solidity",
+                        "comment_node_types": ["comment"]
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let result = config.language_definitions();
+        assert!(matches!(result, Err(ConfigLoadError::LibraryError { .. })));
+    }
+}
@@ -0,0 +1,213 @@
+//! Finds docstrings in languages that document code with string literals or documentation blocks
+//!
+//! Docstrings are ordinary strings in the grammar, so they are recognised by their position in
+//! the tree. Removing a docstring must leave valid source, so a placeholder statement is inserted
+//! when the docstring was the only statement in a body.
+use tree_sitter::Node;
+
+use crate::strip_core::Comment;
+
+/// Determines if a node is a Python docstring
+///
+/// A docstring is a string literal forming the first statement of a module, class or function.
+/// If it is the only statement of a class or function, pass is inserted in its place
+///
+/// # Arguments
+/// * `node` - The node to check
+/// * `source_code` - The source code the node was parsed from
+pub fn python_docstring(node: &Node<'_>, source_code: &str) -> Option<Comment> {
+    if node.kind() != "expression_statement" || node.named_child_count() != 1 {
+        return None;
+    }
+
+    // f-strings are never docstrings
+    let string = node.named_child(0)?;
+    let is_string = match string.kind() {
+        "string" => !source_code[string.byte_range()]
+            .split(['"', '\''])
+            .next()
+            .unwrap_or_default()
+            .contains(['f', 'F']),
+        "concatenated_string" => true,
+        _ => false,
+    };
+    if !is_string {
+        return None;
+    }
+
+    let parent = node.parent()?;
+    let is_body = match parent.kind() {
+        "module" => true,
+        "block" => parent.parent().is_some_and(|definition| {
+            matches!(
+                definition.kind(),
+                "function_definition" | "class_definition"
+            )
+        }),
+        _ => false,
+    };
+    if !is_body {
+        return None;
+    }
+
+    // The docstring must be the first statement. Comments may come before it
+    let mut cursor = parent.walk();
+    let mut statements = parent
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() != "comment");
+    if statements.next() != Some(*node) {
+        return None;
+    }
+    let only_statement = statements.next().is_none();
+
+    // Remove any semicolon separating the docstring from the next statement
+    let end = match node.next_sibling() {
+        Some(semicolon) if semicolon.kind() == ";" => semicolon.end_byte(),
+        _ => node.end_byte(),
+    };
+
+    Some(Comment {
+        range: node.start_byte()..end,
+        placeholder: Some("pass").filter(|_| only_statement && parent.kind() == "block"),
+    })
+}
+
+/// Determines if a node is an Elixir @moduledoc, @doc or @typedoc attribute
+///
+/// The whole attribute is removed. Attributes that hide documentation, such as @doc false, are kept
+///
+/// # Arguments
+/// * `node` - The node to check
+/// * `source_code` - The source code the node was parsed from
+pub fn elixir_docstring(node: &Node<'_>, source_code: &str) -> Option<Comment> {
+    if node.kind() != "unary_operator" || node.child_by_field_name("operator")?.kind() != "@" {
+        return None;
+    }
+
+    let call = node
+        .child_by_field_name("operand")
+        .filter(|operand| operand.kind() == "call")?;
+    let name = call.child_by_field_name("target")?;
+    if !matches!(
+        &source_code[name.byte_range()],
+        "moduledoc" | "doc" | "typedoc"
+    ) {
+        return None;
+    }
+
+    // The documentation is a string or a sigil, e.g. @doc ~S"""
+    let mut cursor = call.walk();
+    let arguments = call
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "arguments")?;
+    let documentation = arguments.named_child(0)?;
+    matches!(documentation.kind(), "string" | "sigil").then(|| node.byte_range().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        languages::find_language, strip_core::strip_comments, strip_options::StripOptions,
+    };
+
+    fn strip_python(source: &str, strip_docstrings: bool) -> String {
+        let options = StripOptions {
+            strip_docstrings,
+            ..Default::default()
+        };
+        strip_comments(find_language("python").unwrap(), source, &options).unwrap()
+    }
+
+    /// GIVEN Python source containing docstrings
+    /// WHEN strip_comments is called without strip_docstrings
+    /// THEN the docstrings are kept
+    #[test]
+    fn python_docstrings_kept_by_default() {
+        let source = "\"\"\"Module\"\"\"\ndef f():\n    \"\"\"Function\"\"\"\n";
+        assert_eq!(strip_python(source, false), source);
+    }
+
+    /// GIVEN Python source containing module, class and function docstrings
+    /// WHEN strip_comments is called with strip_docstrings
+    /// THEN the docstrings are removed, pass is inserted into empty bodies and line numbers
+    /// are unchanged
+    #[test]
+    fn python_docstrings_removed() {
+        let source = concat!(
+            "\"\"\"Module\n",
+            "docstring\"\"\"\n",
+            "class A:\n",
+            "    # comment\n",
+            "    '''Class'''\n",
+            "    def f(self):\n",
+            "        \"\"\"Only\n",
+            "        statement\"\"\"\n",
+            "    def g(self): \"Inline\"; return 1\n",
+            "def h():\n",
+            "    x = 1\n",
+            "    \"\"\"Not a docstring\"\"\"\n",
+            "    f\"\"\"{x} is not a docstring\"\"\"\n",
+        );
+
+        assert_eq!(
+            strip_python(source, true),
+            concat!(
+                "\n",
+                "\n",
+                "class A:\n",
+                "    \n",
+                "    \n",
+                "    def f(self):\n",
+                "        pass\n",
+                "\n",
+                "    def g(self):  return 1\n",
+                "def h():\n",
+                "    x = 1\n",
+                "    \"\"\"Not a docstring\"\"\"\n",
+                "    f\"\"\"{x} is not a docstring\"\"\"\n",
+            )
+        );
+    }
+
+    /// GIVEN Elixir source containing @moduledoc, @doc and @doc false attributes
+    /// WHEN strip_comments is called with and without strip_docstrings
+    /// THEN the documentation is only removed with strip_docstrings, and @doc false is kept
+    #[test]
+    fn elixir_docstrings_removed() {
+        let source = concat!(
+            "defmodule Example do\n",
+            "  @moduledoc \"\"\"\n",
+            "  Module # not a comment\n",
+            "  \"\"\"\n",
+            "  @doc ~S\"Adds one\"\n",
+            "  def add_one(x), do: x + 1 # comment\n",
+            "  @doc false\n",
+            "  def hidden, do: :ok\n",
+            "end\n",
+        );
+        let strip_elixir = |strip_docstrings| {
+            let options = StripOptions {
+                strip_docstrings,
+                ..Default::default()
+            };
+            strip_comments(find_language("elixir").unwrap(), source, &options).unwrap()
+        };
+
+        let stripped = source.replace(" # comment", " ");
+        assert_eq!(strip_elixir(false), stripped);
+        assert_eq!(
+            strip_elixir(true),
+            concat!(
+                "defmodule Example do\n",
+                "  \n",
+                "\n",
+                "\n",
+                "  \n",
+                "  def add_one(x), do: x + 1 \n",
+                "  @doc false\n",
+                "  def hidden, do: :ok\n",
+                "end\n",
+            )
+        );
+    }
+}
@@ -3,9 +3,9 @@ use std::path::PathBuf;
 use glob::{GlobError, PatternError};
 use rayon::ThreadPoolBuildError;
 use thiserror::Error;
-use tree_sitter::LanguageError;
+use tree_sitter::{IncludedRangesError, LanguageError};
 
-use crate::type_hints::TypeHintLoadError;
+use crate::{config::ConfigLoadError, type_hints::TypeHintLoadError};
 
 /// An error raised during the stripping process
 #[derive(Error, Debug)]
@@ -30,6 +30,14 @@ pub enum StrippingError {
     #[error("language error: {0}")]
     LanguageError(#[from] LanguageError),
 
+    // Caused by embedded source with an invalid range
+    #[error("included ranges error: {0}")]
+    IncludedRangesError(#[from] IncludedRangesError),
+
+    // The file is not valid notebook JSON
+    #[error("invalid notebook: {0}")]
+    InvalidNotebook(String),
+
     #[error("IO error: {0}")]
     IOError(#[from] std::io::Error),
 
@@ -48,6 +56,9 @@ pub enum EdStripError {
     #[error("type hint loading error: {0}")]
     TypeHintLoadError(#[from] TypeHintLoadError),
 
+    #[error("config loading error: {0}")]
+    ConfigLoadError(#[from] ConfigLoadError),
+
     #[error("stripping error: {0}")]
     StrippingError(#[from] StrippingError),
 }
@@ -1,280 +1,1899 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use glob::Pattern;
-use once_cell::sync::Lazy;
-use tree_sitter::Language;
+use once_cell::sync::{Lazy, OnceCell};
+use tree_sitter::{Language, Node};
+
+use crate::{
+    docstrings::{elixir_docstring, python_docstring},
+    strip_core::Comment,
+    strip_lexer::{BlockComment, LexerDefinition, LineComment, StringLiteral},
+    strip_options::StripOptions,
+};
 
 /// Defines a supported language
 pub struct LanguageDefinition {
     /// The name of the language
     pub name: &'static str,
 
+    /// A stable identifier for the language
+    /// This should be lower case, and is the preferred way to refer to a language
+    pub id: &'static str,
+
+    /// Alternative names for the language, such as common abbreviations
+    /// These should all be lower case
+    pub aliases: HashSet<&'static str>,
+
     /// The file extensions to strip using this stripper
     /// These should all be lower case
-    pub file_extensions: Lazy<HashSet<&'static str>>,
+    pub file_extensions: HashSet<&'static str>,
 
     /// The path globs to strip using this stripper
-    /// These are more expensive to evaluate, so prefer the file extensions
-    pub path_globs: Lazy<Vec<Pattern>>,
+    /// These are more expensive to evaluate, so prefer the file extensions. Globs are matched
+    /// case insensitively against the whole path, so should start with **/ to match files in any
+    /// directory
+    pub path_globs: Vec<Pattern>,
+
+    /// The prefixes of comments that are documentation, such as /** for Javadoc
+    /// These comments are kept if the keep_doc_comments option is set
+    pub doc_comment_prefixes: &'static [&'static str],
+
+    /// The stripper used to remove comments
+    pub stripper: Stripper,
+}
+
+/// Defines source embedded in another language, such as a script element in HTML
+///
+/// The embedded source is parsed in place, so it is stripped without changing any other byte
+pub struct Injection {
+    /// The node containing the embedded source, e.g. script_element
+    pub node_type: &'static str,
 
-    /// The list of tree-sitter nodes that are comments
-    pub comment_node_types: Lazy<HashSet<&'static str>>,
+    /// The child of the node holding the embedded source, e.g. raw_text
+    /// If None, the node itself holds the embedded source
+    pub content_node_type: Option<&'static str>,
 
-    /// The tree-sitter language
-    pub language: Lazy<Language>,
+    /// Child node types that are not part of the embedded source, such as the
+    /// block quote markers at the start of each line of a Markdown code block
+    pub excluded_node_types: &'static [&'static str],
+
+    /// The language of the embedded source
+    pub language: InjectionLanguage,
+
+    /// If true, all the embedded source is parsed together as a single document
+    /// This is required when the embedded source is split into pieces, such as the HTML
+    /// between the PHP tags of a PHP template
+    pub combined: bool,
+
+    /// If set, the embedded source is only stripped when this returns true for the options
+    pub enabled_by: Option<fn(&StripOptions) -> bool>,
+}
+
+/// Defines how the language of embedded source is selected
+pub enum InjectionLanguage {
+    /// The embedded source is always in the same language
+    Fixed(&'static LanguageDefinition),
+
+    /// The language is selected by an HTML attribute on the node's start tag, e.g. lang="ts"
+    /// Embedded source with other attribute values is not stripped
+    Attribute {
+        /// The attribute name, e.g. lang
+        name: &'static str,
+
+        /// The language of the embedded source when the attribute is absent
+        default: Option<&'static LanguageDefinition>,
+
+        /// The language selected by each attribute value. These should all be lower case
+        languages: HashMap<&'static str, &'static LanguageDefinition>,
+    },
+
+    /// The language is named by the first word of a child node, such as the info string of
+    /// a Markdown code block. Names are found using find_language, so aliases may be used
+    /// Embedded source in unknown languages is not stripped
+    ChildText(&'static str),
 }
 
-static RUST: LanguageDefinition = LanguageDefinition {
+/// Determines if a node is a docstring, such as a string literal documenting a Python function
+/// Docstrings are not comments in the grammar, so each language needs its own rules to find them
+pub type DocstringFinder = fn(&Node<'_>, &str) -> Option<Comment>;
+
+/// Defines how comments are found in a language
+pub enum Stripper {
+    /// Parse the source with a tree-sitter grammar and remove the comment nodes
+    TreeSitter {
+        /// The tree-sitter language
+        language: Language,
+
+        /// The list of tree-sitter nodes that are comments
+        comment_node_types: HashSet<&'static str>,
+
+        /// The comment node types that separate the tokens around them, such as CMake bracket
+        /// comments inside argument lists. These are replaced with a space rather than removed
+        separator_comment_types: HashSet<&'static str>,
+
+        /// Finds docstrings, which are removed if strip_docstrings is set
+        docstrings: Option<DocstringFinder>,
+
+        /// Nodes containing source in other languages, such as script elements in HTML
+        injections: Vec<Injection>,
+    },
+
+    /// Scan the source with a lexer that understands strings and comment markers
+    /// This is for simple formats that have no tree-sitter grammar
+    Lexer(LexerDefinition),
+
+    /// Strip the code cells of a Jupyter notebook, leaving the rest of the JSON unchanged
+    Notebook,
+
+    /// Copy the file byte-for-byte without parsing it
+    Null,
+}
+
+static RUST: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Rust",
-    comment_node_types: Lazy::new(|| ["line_comment", "block_comment", "doc_comment"].into()),
-    file_extensions: Lazy::new(|| ["rs"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(tree_sitter_rust::language),
-};
+    id: "rust",
+    aliases: ["rs"].into(),
+    file_extensions: ["rs"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "//!", "/**", "/*!"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_rust::language(),
+        comment_node_types: ["line_comment", "block_comment", "doc_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static TYPESCRIPT: LanguageDefinition = LanguageDefinition {
+static TYPESCRIPT: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Typescript",
-    // TBD: Suspect html_comment isn't required. It's defined in the node types, but surely a TSX thing?
-    comment_node_types: Lazy::new(|| ["comment", "html_comment"].into()),
-    file_extensions: Lazy::new(|| ["ts", "mts"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
-};
+    id: "typescript",
+    aliases: ["ts"].into(),
+    file_extensions: ["ts", "mts"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        // TBD: Suspect html_comment isn't required. It's defined in the node types, but surely a TSX thing?
+        comment_node_types: ["comment", "html_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static TYPESCRIPT_REACT: LanguageDefinition = LanguageDefinition {
+static TYPESCRIPT_REACT: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Typescript with React",
-    comment_node_types: Lazy::new(|| ["comment", "html_comment"].into()),
-    file_extensions: Lazy::new(|| ["tsx"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_typescript::LANGUAGE_TSX.into()),
-};
+    id: "typescript-react",
+    aliases: ["tsx"].into(),
+    file_extensions: ["tsx"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_typescript::LANGUAGE_TSX.into(),
+        comment_node_types: ["comment", "html_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static JAVASCRIPT: LanguageDefinition = LanguageDefinition {
+static JAVASCRIPT: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Javascript",
-    comment_node_types: Lazy::new(|| ["comment", "html_comment"].into()),
-    file_extensions: Lazy::new(|| ["js", "mjs", "cjs", "jsx"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_javascript::LANGUAGE.into()),
-};
+    id: "javascript",
+    aliases: ["js", "jsx"].into(),
+    file_extensions: ["js", "mjs", "cjs", "jsx"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_javascript::LANGUAGE.into(),
+        comment_node_types: ["comment", "html_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static GO: LanguageDefinition = LanguageDefinition {
+static GO: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Go",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["go"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_go::LANGUAGE.into()),
-};
+    id: "go",
+    aliases: ["golang"].into(),
+    file_extensions: ["go"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_go::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static PYTHON: LanguageDefinition = LanguageDefinition {
+static PYTHON: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Python",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["py"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_python::LANGUAGE.into()),
-};
+    id: "python",
+    aliases: ["py", "python3"].into(),
+    file_extensions: ["py"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_python::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: Some(python_docstring),
+        injections: vec![],
+    },
+});
 
-static CPP: LanguageDefinition = LanguageDefinition {
+static CPP: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "C++",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["cpp", "cc", "cxx", "h", "hxx", "hpp"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_cpp::LANGUAGE.into()),
-};
+    id: "cpp",
+    aliases: ["c++", "cxx"].into(),
+    file_extensions: ["cpp", "cc", "cxx", "h", "hxx", "hpp"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "//!", "/**", "/*!"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_cpp::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static C: LanguageDefinition = LanguageDefinition {
+static C: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "C",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["c", "h"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_c::LANGUAGE.into()),
-};
+    id: "c",
+    aliases: [].into(),
+    file_extensions: ["c", "h"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "//!", "/**", "/*!"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_c::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static BASH: LanguageDefinition = LanguageDefinition {
+static BASH: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Bash",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["sh"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_bash::LANGUAGE.into()),
-};
+    id: "bash",
+    aliases: ["sh", "shell"].into(),
+    file_extensions: ["sh"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_bash::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static XML: LanguageDefinition = LanguageDefinition {
+static XML: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "XML",
-    comment_node_types: Lazy::new(|| ["Comment"].into()),
-    file_extensions: Lazy::new(|| ["xml", "vcxproj"].into()),
-    path_globs: Lazy::new(|| {
-        vec![
-            Pattern::from_str("**/*.vcxproj.filters").unwrap(),
-            Pattern::from_str("**/*.vcxproj.user").unwrap(),
-        ]
-    }),
-    language: Lazy::new(|| tree_sitter_xml::LANGUAGE_XML.into()),
-};
+    id: "xml",
+    aliases: [].into(),
+    file_extensions: ["xml", "vcxproj"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/*.vcxproj.filters").unwrap(),
+        Pattern::from_str("**/*.vcxproj.user").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_xml::LANGUAGE_XML.into(),
+        comment_node_types: ["Comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
 // This library has a [patch] section in cargo.toml
-static OBJECTIVE_C: LanguageDefinition = LanguageDefinition {
+static OBJECTIVE_C: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Objective-C",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["m", "mm", "h"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(tree_sitter_objc::language),
-};
+    id: "objective-c",
+    aliases: ["objc", "obj-c"].into(),
+    file_extensions: ["m", "mm", "h"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "//!", "/**", "/*!"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_objc::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static JAVA: LanguageDefinition = LanguageDefinition {
+static JAVA: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Java",
-    comment_node_types: Lazy::new(|| ["block_comment", "line_comment"].into()),
-    file_extensions: Lazy::new(|| ["java"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_java::LANGUAGE.into()),
-};
+    id: "java",
+    aliases: [].into(),
+    file_extensions: ["java"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_java::LANGUAGE.into(),
+        comment_node_types: ["block_comment", "line_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static HTML: LanguageDefinition = LanguageDefinition {
+static HTML: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "HTML",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["htm", "html"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_html::LANGUAGE.into()),
-};
+    id: "html",
+    aliases: ["htm"].into(),
+    file_extensions: ["htm", "html"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_html::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![
+            Injection {
+                node_type: "script_element",
+                content_node_type: Some("raw_text"),
+                excluded_node_types: &[],
+                language: InjectionLanguage::Attribute {
+                    name: "type",
+                    default: Some(&*JAVASCRIPT),
+                    languages: [
+                        ("text/javascript", &*JAVASCRIPT),
+                        ("application/javascript", &*JAVASCRIPT),
+                        ("text/ecmascript", &*JAVASCRIPT),
+                        ("application/ecmascript", &*JAVASCRIPT),
+                        ("module", &*JAVASCRIPT),
+                    ]
+                    .into(),
+                },
+                combined: false,
+                enabled_by: None,
+            },
+            Injection {
+                node_type: "style_element",
+                content_node_type: Some("raw_text"),
+                excluded_node_types: &[],
+                language: InjectionLanguage::Attribute {
+                    name: "type",
+                    default: Some(&*CSS),
+                    languages: [("text/css", &*CSS)].into(),
+                },
+                combined: false,
+                enabled_by: None,
+            },
+        ],
+    },
+});
+
+/// The script and style blocks of a single-file component, such as a Vue or Svelte file
+/// The language of each block is selected by its lang attribute
+fn single_file_component_injections() -> Vec<Injection> {
+    vec![
+        Injection {
+            node_type: "script_element",
+            content_node_type: Some("raw_text"),
+            excluded_node_types: &[],
+            language: InjectionLanguage::Attribute {
+                name: "lang",
+                default: Some(&*JAVASCRIPT),
+                languages: [
+                    ("js", &*JAVASCRIPT),
+                    ("javascript", &*JAVASCRIPT),
+                    ("jsx", &*JAVASCRIPT),
+                    ("ts", &*TYPESCRIPT),
+                    ("typescript", &*TYPESCRIPT),
+                    ("tsx", &*TYPESCRIPT_REACT),
+                ]
+                .into(),
+            },
+            combined: false,
+            enabled_by: None,
+        },
+        Injection {
+            node_type: "style_element",
+            content_node_type: Some("raw_text"),
+            excluded_node_types: &[],
+            language: InjectionLanguage::Attribute {
+                name: "lang",
+                default: Some(&*CSS),
+                // The CSS grammar does not understand the // comments in SCSS and Less,
+                // but still finds their /* */ comments
+                languages: [
+                    ("css", &*CSS),
+                    ("postcss", &*CSS),
+                    ("scss", &*CSS),
+                    ("less", &*CSS),
+                ]
+                .into(),
+            },
+            combined: false,
+            enabled_by: None,
+        },
+    ]
+}
+
+// Single-file components are HTML-like, so the template is stripped as HTML
+static VUE: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Vue",
+    id: "vue",
+    aliases: [].into(),
+    file_extensions: ["vue"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_html::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: single_file_component_injections(),
+    },
+});
+
+static SVELTE: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Svelte",
+    id: "svelte",
+    aliases: [].into(),
+    file_extensions: ["svelte"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_html::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: single_file_component_injections(),
+    },
+});
 
 // There's also a 'LANGUAGE_PHP_ONLY' mode.
 // It's unclear what the difference - I suspect that it only allows PHP,
-// as opposed to the more common usage of PHP as a templating solution
-static PHP: LanguageDefinition = LanguageDefinition {
+// as opposed to the more common usage of PHP as a templating solution.
+// The template text between PHP tags is stripped as a single HTML document
+static PHP: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "PHP",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["php"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_php::LANGUAGE_PHP.into()),
-};
+    id: "php",
+    aliases: [].into(),
+    file_extensions: ["php"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_php::LANGUAGE_PHP.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![Injection {
+            node_type: "text",
+            content_node_type: None,
+            excluded_node_types: &[],
+            language: InjectionLanguage::Fixed(&HTML),
+            combined: true,
+            enabled_by: None,
+        }],
+    },
+});
 
 // Unclear what version of Lua this is
-static LUA: LanguageDefinition = LanguageDefinition {
+static LUA: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Lua",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["lua"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_lua::LANGUAGE.into()),
-};
+    id: "lua",
+    aliases: [].into(),
+    file_extensions: ["lua"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_lua::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static SWIFT: LanguageDefinition = LanguageDefinition {
+static SWIFT: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Swift",
-    comment_node_types: Lazy::new(|| ["comment", "multiline_comment"].into()),
-    file_extensions: Lazy::new(|| ["swift"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_swift::LANGUAGE.into()),
-};
+    id: "swift",
+    aliases: [].into(),
+    file_extensions: ["swift"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_swift::LANGUAGE.into(),
+        comment_node_types: ["comment", "multiline_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static YAML: LanguageDefinition = LanguageDefinition {
+static YAML: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "YAML",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["yaml", "yml"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(tree_sitter_yaml::language),
-};
+    id: "yaml",
+    aliases: ["yml"].into(),
+    file_extensions: ["yaml", "yml"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_yaml::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static RUBY: LanguageDefinition = LanguageDefinition {
+static RUBY: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Ruby",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["rb"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_ruby::LANGUAGE.into()),
-};
+    id: "ruby",
+    aliases: ["rb"].into(),
+    file_extensions: ["rb"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_ruby::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static TOML: LanguageDefinition = LanguageDefinition {
+static TOML: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "TOML",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["toml"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_toml::language()),
-};
+    id: "toml",
+    aliases: [].into(),
+    file_extensions: ["toml"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_toml::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static KOTLIN: LanguageDefinition = LanguageDefinition {
+static KOTLIN: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Kotlin",
-    comment_node_types: Lazy::new(|| ["line_comment", "multiline_comment"].into()),
-    file_extensions: Lazy::new(|| ["kt", "kts"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(tree_sitter_kotlin::language),
-};
+    id: "kotlin",
+    aliases: ["kt"].into(),
+    file_extensions: ["kt", "kts"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_kotlin::language(),
+        comment_node_types: ["line_comment", "multiline_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
+
+static GROOVY: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Groovy",
+    id: "groovy",
+    aliases: ["gradle", "jenkinsfile"].into(),
+    file_extensions: ["groovy", "gvy", "gy", "gsh", "gradle", "jenkinsfile"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/Jenkinsfile").unwrap(),
+        Pattern::from_str("**/Jenkinsfile.*").unwrap(),
+    ],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_groovy::LANGUAGE.into(),
+        comment_node_types: ["line_comment", "block_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
+
+static SCALA: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Scala",
+    id: "scala",
+    aliases: ["sbt"].into(),
+    file_extensions: ["scala", "sc", "sbt"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_scala::LANGUAGE.into(),
+        comment_node_types: ["comment", "block_comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
+
+/// The POD commands that start a documentation block in Perl
+const PERL_POD_COMMANDS: [&str; 9] = [
+    "=pod",
+    "=head",
+    "=over",
+    "=item",
+    "=back",
+    "=begin",
+    "=end",
+    "=for",
+    "=encoding",
+];
 
-static PROTO: LanguageDefinition = LanguageDefinition {
+static PERL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Perl",
+    id: "perl",
+    aliases: ["pl"].into(),
+    file_extensions: ["pl", "pm", "t"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    // There is no Perl grammar for the tree-sitter version used here, so Perl is lexed
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#")],
+        // POD blocks are documentation, and are only removed with strip_docstrings
+        block_comments: PERL_POD_COMMANDS
+            .iter()
+            .map(|command| BlockComment {
+                line_start_only: true,
+                documentation: true,
+                ..BlockComment::new(command, "\n=cut")
+            })
+            .collect(),
+        strings: vec![
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("\"")
+            },
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("'")
+            },
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("`")
+            },
+            // The last index of an array, e.g. $#array, is not a comment
+            StringLiteral::verbatim("$#", ""),
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: true,
+        quote_like_operators: true,
+        // Everything after __END__ or __DATA__ is data, so it is kept verbatim
+        end_markers: vec!["__END__".into(), "__DATA__".into()],
+    }),
+});
+
+static R: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "R",
+    id: "r",
+    aliases: [].into(),
+    file_extensions: ["r"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["#'"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_r::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
+
+static HASKELL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Haskell",
+    id: "haskell",
+    aliases: ["hs"].into(),
+    file_extensions: ["hs"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["-- |", "-- ^", "-- $", "{- |", "{- ^"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_haskell::LANGUAGE.into(),
+        // Pragmas such as {-# LANGUAGE ... #-} change compilation. They are pragma nodes rather
+        // than comments, so are kept
+        comment_node_types: ["comment", "haddock"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
+
+static ELIXIR: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Elixir",
+    id: "elixir",
+    aliases: ["ex", "exs"].into(),
+    file_extensions: ["ex", "exs"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_elixir::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: Some(elixir_docstring),
+        injections: vec![],
+    },
+});
+
+static PROTO: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Protobuf",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["pb", "proto"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_proto::LANGUAGE.into()),
-};
+    id: "protobuf",
+    aliases: ["proto"].into(),
+    // .pb files are usually binary encoded messages rather than source, so are not included
+    file_extensions: ["proto"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_proto::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static C_SHARP: LanguageDefinition = LanguageDefinition {
+static C_SHARP: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "C#",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["cs"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_c_sharp::LANGUAGE.into()),
-};
+    id: "csharp",
+    aliases: ["c#", "cs"].into(),
+    file_extensions: ["cs"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "/**"],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_c_sharp::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static POWERSHELL: LanguageDefinition = LanguageDefinition {
+static POWERSHELL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Powershell",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["ps1"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(tree_sitter_powershell::language),
-};
+    id: "powershell",
+    aliases: ["ps", "pwsh", "ps1"].into(),
+    file_extensions: ["ps1"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_powershell::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static DOCKERFILE: LanguageDefinition = LanguageDefinition {
+static DOCKERFILE: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Dockerfile",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["dockerfile"].into()),
-    path_globs: Lazy::new(|| {
-        vec![
-            Pattern::from_str("**/DockerFile").unwrap(),
-            Pattern::from_str("**/DockerFile.*").unwrap(),
-        ]
-    }),
-    language: Lazy::new(tree_sitter_dockerfile::language),
-};
+    id: "dockerfile",
+    aliases: ["docker"].into(),
+    // The extensions match suffix-named files such as app.Dockerfile
+    file_extensions: ["dockerfile", "containerfile"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/Dockerfile").unwrap(),
+        Pattern::from_str("**/Dockerfile.*").unwrap(),
+        Pattern::from_str("**/Containerfile").unwrap(),
+        Pattern::from_str("**/Containerfile.*").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_dockerfile::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static CSS: LanguageDefinition = LanguageDefinition {
+static CSS: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "CSS",
-    // There's also a js_comment, but that's not valid in CSS. Odd!
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["css"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_css::LANGUAGE.into()),
-};
+    id: "css",
+    aliases: [].into(),
+    file_extensions: ["css"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_css::LANGUAGE.into(),
+        // There's also a js_comment, but that's not valid in CSS. Odd!
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static CMAKE: LanguageDefinition = LanguageDefinition {
+static CMAKE: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "CMake",
-    comment_node_types: Lazy::new(|| ["line_comment", "bracket_comment"].into()),
-    file_extensions: Lazy::new(|| [].into()),
-    path_globs: Lazy::new(|| vec![Pattern::from_str("**/CMakeLists.txt").unwrap()]),
-    language: Lazy::new(tree_sitter_cmake::language),
-};
+    id: "cmake",
+    aliases: [].into(),
+    // .cmake covers modules, toolchain files and package config files
+    file_extensions: ["cmake"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/CMakeLists.txt").unwrap(),
+        Pattern::from_str("**/*.cmake.in").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_cmake::language(),
+        comment_node_types: ["line_comment", "bracket_comment"].into(),
+        // A#[[comment]]B is two arguments, so removing the comment must not join them
+        separator_comment_types: ["bracket_comment"].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static HCL: LanguageDefinition = LanguageDefinition {
+static HCL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "HCL",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["tf"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(tree_sitter_hcl::language),
-};
+    id: "hcl",
+    aliases: ["terraform", "tf", "terragrunt", "packer"].into(),
+    // .hcl also covers terragrunt.hcl, Packer .pkr.hcl and Nomad job files
+    file_extensions: ["tf", "tfvars", "hcl"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_hcl::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static MAKE: LanguageDefinition = LanguageDefinition {
+static MAKE: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Make",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["mk"].into()),
-    path_globs: Lazy::new(|| vec![Pattern::from_str("makefile").unwrap()]),
-    language: Lazy::new(tree_sitter_make::language),
-};
+    id: "make",
+    aliases: ["makefile"].into(),
+    file_extensions: ["mk", "make"].into(),
+    // Makefile.* matches templates such as Makefile.in and Makefile.am. Files such as
+    // Makefile.toml are identified by their extension instead
+    path_globs: vec![
+        Pattern::from_str("**/Makefile").unwrap(),
+        Pattern::from_str("**/Makefile.*").unwrap(),
+        Pattern::from_str("**/GNUmakefile").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_make::language(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
 
-static INI: LanguageDefinition = LanguageDefinition {
+static INI: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
     name: "Ini",
-    comment_node_types: Lazy::new(|| ["comment"].into()),
-    file_extensions: Lazy::new(|| ["ini"].into()),
-    path_globs: Lazy::new(|| vec![]),
-    language: Lazy::new(|| tree_sitter_ini::LANGUAGE.into()),
-};
+    id: "ini",
+    aliases: [].into(),
+    file_extensions: ["ini"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_ini::LANGUAGE.into(),
+        comment_node_types: ["comment"].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![],
+    },
+});
+
+// Markdown has no comments of its own. Fenced code blocks are stripped using the language named
+// by their info string, and HTML comments are optionally removed from the Markdown itself
+static MARKDOWN: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Markdown",
+    id: "markdown",
+    aliases: ["md"].into(),
+    // R Markdown and Quarto documents are Markdown with {r} style info strings
+    file_extensions: ["md", "markdown", "rmd", "qmd"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_md::LANGUAGE.into(),
+        comment_node_types: [].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![
+            Injection {
+                node_type: "fenced_code_block",
+                content_node_type: Some("code_fence_content"),
+                // Code blocks inside block quotes have a > at the start of each line
+                excluded_node_types: &["block_continuation"],
+                language: InjectionLanguage::ChildText("info_string"),
+                combined: false,
+                enabled_by: None,
+            },
+            Injection {
+                node_type: "html_block",
+                content_node_type: None,
+                excluded_node_types: &["block_continuation"],
+                language: InjectionLanguage::Fixed(&HTML),
+                combined: false,
+                enabled_by: Some(|options| options.markdown_html_comments),
+            },
+            Injection {
+                node_type: "inline",
+                content_node_type: None,
+                excluded_node_types: &["block_continuation"],
+                language: InjectionLanguage::Fixed(&MARKDOWN_INLINE),
+                combined: false,
+                enabled_by: Some(|options| options.markdown_html_comments),
+            },
+        ],
+    },
+});
+
+// The inline content of Markdown paragraphs and headings. This is only used by MARKDOWN
+static MARKDOWN_INLINE: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Markdown inline",
+    id: "markdown-inline",
+    aliases: [].into(),
+    file_extensions: [].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::TreeSitter {
+        language: tree_sitter_md::INLINE_LANGUAGE.into(),
+        comment_node_types: [].into(),
+        separator_comment_types: [].into(),
+        docstrings: None,
+        injections: vec![Injection {
+            node_type: "html_tag",
+            content_node_type: None,
+            excluded_node_types: &[],
+            language: InjectionLanguage::Fixed(&HTML),
+            combined: false,
+            enabled_by: None,
+        }],
+    },
+});
+
+// Jupyter notebooks. Code cells are stripped using the language of the notebook's kernel
+static JUPYTER_NOTEBOOK: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Jupyter notebook",
+    id: "jupyter",
+    aliases: ["ipynb", "notebook"].into(),
+    file_extensions: ["ipynb"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Notebook,
+});
+
+// The following languages have no tree-sitter grammar, and are stripped by a lexer instead
+
+// A basic stripper for files with # line comments. Select it using a type hint
+static HASH_COMMENTS: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Hash comments",
+    id: "hash-comments",
+    aliases: ["hash"].into(),
+    file_extensions: [].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#")],
+        block_comments: vec![],
+        strings: vec![StringLiteral::new("\""), StringLiteral::new("'")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// A basic stripper for files with // and /* */ comments. Select it using a type hint
+static C_STYLE_COMMENTS: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "C-style comments",
+    id: "c-style-comments",
+    aliases: ["c-style"].into(),
+    file_extensions: [].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("//")],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\""), StringLiteral::new("'")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Java .properties files only have comments at the start of a line
+static PROPERTIES: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Properties",
+    id: "properties",
+    aliases: [].into(),
+    file_extensions: ["properties"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::line_start("#"), LineComment::line_start("!")],
+        block_comments: vec![],
+        strings: vec![],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// .gitconfig and friends allow trailing comments outside of quoted values
+static GITCONFIG: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Git config",
+    id: "gitconfig",
+    aliases: [].into(),
+    file_extensions: ["gitconfig"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/.gitconfig").unwrap(),
+        Pattern::from_str("**/.git/config").unwrap(),
+        Pattern::from_str("**/.gitmodules").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#"), LineComment::new(";")],
+        block_comments: vec![],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+static EDITORCONFIG: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "EditorConfig",
+    id: "editorconfig",
+    aliases: [].into(),
+    file_extensions: [].into(),
+    path_globs: vec![Pattern::from_str("**/.editorconfig").unwrap()],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::line_start("#"), LineComment::line_start(";")],
+        block_comments: vec![],
+        strings: vec![],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+static NGINX: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "nginx",
+    id: "nginx",
+    aliases: [].into(),
+    file_extensions: [].into(),
+    path_globs: vec![
+        Pattern::from_str("**/nginx.conf").unwrap(),
+        Pattern::from_str("**/nginx/**/*.conf").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#")],
+        block_comments: vec![],
+        strings: vec![StringLiteral::new("\""), StringLiteral::new("'")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Windows batch files. REM is a command, so it is only a comment at the start of a line, and
+// never inside an echo or a string. :: is a label that is never jumped to
+static BATCH: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Batch",
+    id: "batch",
+    aliases: ["bat", "cmd"].into(),
+    file_extensions: ["bat", "cmd"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![
+            LineComment {
+                ignore_case: true,
+                whole_word: true,
+                ..LineComment::line_start("rem")
+            },
+            LineComment {
+                ignore_case: true,
+                whole_word: true,
+                ..LineComment::line_start("@rem")
+            },
+            LineComment::line_start("::"),
+        ],
+        block_comments: vec![],
+        strings: vec![],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+/// A # comment that is not a C preprocessor directive, such as #define or #include
+fn preprocessor_aware_hash_comment(line_start_only: bool) -> LineComment {
+    LineComment {
+        line_start_only,
+        not_followed_by: [
+            "define", "undef", "include", "if", "ifdef", "ifndef", "elif", "else", "endif",
+            "error", "warning", "pragma", "line",
+        ]
+        .map(String::from)
+        .into(),
+        ..LineComment::new("#")
+    }
+}
+
+// GNU assembler. The comment character depends on the target, so by default # is only a comment
+// at the start of a line, where it is valid for every target. On ARM, a # elsewhere is an
+// immediate operand. .S files are run through the C preprocessor, so directives are kept
+static GNU_ASSEMBLER: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "GNU assembler",
+    id: "gas",
+    aliases: ["gnu-as"].into(),
+    file_extensions: ["s"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![
+            preprocessor_aware_hash_comment(true),
+            LineComment::new("//"),
+        ],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// GNU assembler for x86, where # starts a comment anywhere on a line. Select it using a type hint
+static GNU_ASSEMBLER_X86: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "GNU assembler (x86)",
+    id: "gas-x86",
+    aliases: ["att"].into(),
+    file_extensions: [].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![
+            preprocessor_aware_hash_comment(false),
+            LineComment::new("//"),
+        ],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// GNU assembler for 32 bit ARM, where @ starts a comment. Select it using a type hint
+static GNU_ASSEMBLER_ARM: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "GNU assembler (ARM)",
+    id: "gas-arm",
+    aliases: ["arm-asm"].into(),
+    file_extensions: [].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![
+            preprocessor_aware_hash_comment(true),
+            LineComment::new("@"),
+            LineComment::new("//"),
+        ],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// NASM (and other Intel syntax assemblers) only have ; comments
+static NASM: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "NASM",
+    id: "nasm",
+    aliases: ["yasm", "masm"].into(),
+    file_extensions: ["asm"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new(";")],
+        block_comments: vec![],
+        strings: vec![
+            StringLiteral {
+                escape: None,
+                ..StringLiteral::new("\"")
+            },
+            StringLiteral {
+                escape: None,
+                ..StringLiteral::new("'")
+            },
+            StringLiteral::new("`"),
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Protobuf messages in text format, as used for test data and configuration
+static PROTO_TEXT: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Protobuf text format",
+    id: "textproto",
+    aliases: ["pbtxt"].into(),
+    file_extensions: ["textproto", "textpb", "txtpb", "pbtxt"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#")],
+        block_comments: vec![],
+        strings: vec![StringLiteral::new("\""), StringLiteral::new("'")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Thrift IDL allows #, // and /* */ comments
+static THRIFT: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Thrift",
+    id: "thrift",
+    aliases: [].into(),
+    file_extensions: ["thrift"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#"), LineComment::new("//")],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\""), StringLiteral::new("'")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+static FLATBUFFERS: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "FlatBuffers",
+    id: "flatbuffers",
+    aliases: ["fbs"].into(),
+    file_extensions: ["fbs"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("//")],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Cap'n Proto only has # comments
+static CAPNP: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Cap'n Proto",
+    id: "capnp",
+    aliases: ["capnproto"].into(),
+    file_extensions: ["capnp"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#")],
+        block_comments: vec![],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// JSON with comments, as used by VS Code and the TypeScript compiler. These files usually end in
+// .json, so the well known ones are matched by name
+static JSONC: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "JSON with comments",
+    id: "jsonc",
+    aliases: [].into(),
+    file_extensions: ["jsonc", "code-workspace"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/tsconfig.json").unwrap(),
+        Pattern::from_str("**/tsconfig.*.json").unwrap(),
+        Pattern::from_str("**/jsconfig.json").unwrap(),
+        Pattern::from_str("**/jsconfig.*.json").unwrap(),
+        Pattern::from_str("**/.vscode/*.json").unwrap(),
+        Pattern::from_str("**/devcontainer.json").unwrap(),
+        Pattern::from_str("**/.devcontainer.json").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("//")],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: true,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// JSON5 also allows single quoted strings, which may continue over lines with a \
+static JSON5: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "JSON5",
+    id: "json5",
+    aliases: [].into(),
+    file_extensions: ["json5"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("//")],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("\"")
+            },
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("'")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: true,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Visual Studio solutions have # comment lines. The # Visual Studio Version line following the
+// format version header selects which version of Visual Studio opens the solution, so it is kept
+static SOLUTION: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Visual Studio solution",
+    id: "sln",
+    aliases: ["solution"].into(),
+    file_extensions: ["sln"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment {
+            not_followed_by: vec!["Visual".into()],
+            ..LineComment::line_start("#")
+        }],
+        block_comments: vec![],
+        strings: vec![],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Linker module-definition files
+static MODULE_DEFINITION: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Module-definition",
+    id: "def",
+    aliases: ["module-definition"].into(),
+    file_extensions: ["def"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new(";")],
+        block_comments: vec![],
+        strings: vec![
+            StringLiteral::verbatim("\"", "\""),
+            StringLiteral::verbatim("'", "'"),
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// systemd unit files only have full line comments, so a # or ; inside a value is never a comment.
+// Drop-in .conf files are matched by path
+static SYSTEMD: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "systemd unit",
+    id: "systemd",
+    aliases: ["systemd-unit"].into(),
+    file_extensions: [
+        "service",
+        "socket",
+        "timer",
+        "mount",
+        "automount",
+        "swap",
+        "target",
+        "path",
+        "slice",
+        "scope",
+        "network",
+        "netdev",
+        "link",
+    ]
+    .into(),
+    path_globs: vec![Pattern::from_str("**/systemd/**/*.conf").unwrap()],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::line_start("#"), LineComment::line_start(";")],
+        block_comments: vec![],
+        strings: vec![],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Desktop entries only have full line # comments. ; separates the items of a list
+static DESKTOP_ENTRY: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Desktop entry",
+    id: "desktop",
+    aliases: ["desktop-entry"].into(),
+    file_extensions: ["desktop", "directory"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::line_start("#")],
+        block_comments: vec![],
+        strings: vec![],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// SQL as understood by most databases. Strings may span lines, and quotes are escaped by doubling
+// them, which the lexer sees as two adjacent strings. MySQL executable comments (/*! ... */) are
+// kept, as they change the behaviour of dumps. Use a type hint to select a specific dialect
+static SQL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "SQL",
+    id: "sql",
+    aliases: [].into(),
+    file_extensions: ["sql"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("--")],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![
+            StringLiteral::verbatim("'", "'"),
+            StringLiteral::verbatim("\"", "\""),
+            StringLiteral::verbatim("`", "`"),
+            StringLiteral::verbatim("/*!", "*/"),
+        ],
+        dollar_quoted_strings: true,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// PostgreSQL allows block comments to be nested
+static POSTGRESQL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "PostgreSQL",
+    id: "postgresql",
+    aliases: ["postgres", "pgsql", "plpgsql"].into(),
+    file_extensions: ["pgsql"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("--")],
+        block_comments: vec![BlockComment {
+            start: "/*".into(),
+            end: "*/".into(),
+            nested: true,
+            line_start_only: false,
+            documentation: false,
+            ignore_case: false,
+            not_followed_by: vec![],
+        }],
+        // E'...' escape strings use \ escapes, so E'it\'s' is a single string
+        strings: vec![
+            StringLiteral {
+                end: "'".into(),
+                multiline: true,
+                ..StringLiteral::new("E'")
+            },
+            StringLiteral {
+                end: "'".into(),
+                multiline: true,
+                ..StringLiteral::new("e'")
+            },
+            StringLiteral::verbatim("'", "'"),
+            StringLiteral::verbatim("\"", "\""),
+        ],
+        dollar_quoted_strings: true,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// MySQL also has # comments, but -- must be followed by whitespace or the end of the file.
+// Strings use \ escapes.
+// Executable comments (/*! ... */) and optimizer hints (/*+ ... */) are kept
+static MYSQL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "MySQL",
+    id: "mysql",
+    aliases: ["mariadb"].into(),
+    file_extensions: [].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![
+            LineComment::new("#"),
+            LineComment {
+                followed_by_whitespace: true,
+                ..LineComment::new("--")
+            },
+        ],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![
+            StringLiteral {
+                escape: Some('\\'),
+                ..StringLiteral::verbatim("'", "'")
+            },
+            StringLiteral {
+                escape: Some('\\'),
+                ..StringLiteral::verbatim("\"", "\"")
+            },
+            StringLiteral::verbatim("`", "`"),
+            StringLiteral::verbatim("/*!", "*/"),
+            StringLiteral::verbatim("/*+", "*/"),
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+/// The words that start synthesis and lint directive comments in hardware description languages,
+/// such as // synopsys translate_off. These change the synthesized hardware, so are kept
+const HDL_DIRECTIVES: [&str; 12] = [
+    "synopsys",
+    "synthesis",
+    "pragma",
+    "translate_off",
+    "translate_on",
+    "cadence",
+    "ambit",
+    "altera",
+    "xilinx",
+    "exemplar",
+    "rtl_synthesis",
+    "verilator",
+];
+
+/// The comment syntax of Verilog and SystemVerilog. (* *) attributes are not comments, so are kept
+fn verilog_lexer() -> LexerDefinition {
+    LexerDefinition {
+        line_comments: vec![LineComment {
+            not_followed_by: HDL_DIRECTIVES.map(String::from).into(),
+            ..LineComment::new("//")
+        }],
+        block_comments: vec![BlockComment {
+            not_followed_by: HDL_DIRECTIVES.map(String::from).into(),
+            ..BlockComment::new("/*", "*/")
+        }],
+        strings: vec![StringLiteral::new("\"")],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }
+}
+
+static VERILOG: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Verilog",
+    id: "verilog",
+    aliases: [].into(),
+    file_extensions: ["v", "vh"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(verilog_lexer()),
+});
+
+static SYSTEM_VERILOG: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "SystemVerilog",
+    id: "systemverilog",
+    aliases: ["sv"].into(),
+    file_extensions: ["sv", "svh"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(verilog_lexer()),
+});
+
+// VHDL strings double their quotes rather than escaping them. ' is also used for attributes,
+// e.g. a'length, so only the character literal '"' is recognised, to stop it starting a string
+static VHDL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "VHDL",
+    id: "vhdl",
+    aliases: [].into(),
+    file_extensions: ["vhd", "vhdl"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        // VHDL is case insensitive, so directives such as -- Synopsys translate_off are too
+        line_comments: vec![LineComment {
+            ignore_case: true,
+            not_followed_by: HDL_DIRECTIVES.map(String::from).into(),
+            ..LineComment::new("--")
+        }],
+        block_comments: vec![BlockComment {
+            ignore_case: true,
+            not_followed_by: HDL_DIRECTIVES.map(String::from).into(),
+            ..BlockComment::new("/*", "*/")
+        }],
+        strings: vec![
+            StringLiteral::verbatim("'\"", "'"),
+            StringLiteral {
+                escape: None,
+                ..StringLiteral::new("\"")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Dart block comments nest. Strings may contain ${} interpolations, unless they are raw strings
+static DART: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Dart",
+    id: "dart",
+    aliases: ["flutter"].into(),
+    file_extensions: ["dart"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "/**"],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("//")],
+        block_comments: vec![BlockComment {
+            nested: true,
+            ..BlockComment::new("/*", "*/")
+        }],
+        strings: vec![
+            StringLiteral::verbatim("r\"\"\"", "\"\"\""),
+            StringLiteral::verbatim("r'''", "'''"),
+            StringLiteral {
+                multiline: false,
+                ..StringLiteral::verbatim("r\"", "\"")
+            },
+            StringLiteral {
+                multiline: false,
+                ..StringLiteral::verbatim("r'", "'")
+            },
+            StringLiteral {
+                escape: Some('\\'),
+                interpolation: Some("${".into()),
+                ..StringLiteral::verbatim("\"\"\"", "\"\"\"")
+            },
+            StringLiteral {
+                escape: Some('\\'),
+                interpolation: Some("${".into()),
+                ..StringLiteral::verbatim("'''", "'''")
+            },
+            StringLiteral {
+                interpolation: Some("${".into()),
+                ..StringLiteral::new("\"")
+            },
+            StringLiteral {
+                interpolation: Some("${".into()),
+                ..StringLiteral::new("'")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Zig multiline strings run from \\ to the end of the line
+static ZIG: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Zig",
+    id: "zig",
+    aliases: [].into(),
+    file_extensions: ["zig", "zon"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["///", "//!"],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("//")],
+        block_comments: vec![],
+        strings: vec![
+            StringLiteral::new("\""),
+            StringLiteral::new("'"),
+            StringLiteral {
+                multiline: false,
+                ..StringLiteral::verbatim("\\\\", "\n")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Nix strings may contain ${} interpolations, which may contain strings and comments of their own.
+// .nix covers flake.nix, default.nix and shell.nix, wherever they are in the tree
+static NIX: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Nix",
+    id: "nix",
+    aliases: [].into(),
+    file_extensions: ["nix"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &["/**"],
+    stripper: Stripper::Lexer(LexerDefinition {
+        // Unquoted URIs such as https://example.com/a#b are strings, so # only starts a comment
+        // after whitespace
+        line_comments: vec![LineComment {
+            preceded_by_whitespace: true,
+            ..LineComment::new("#")
+        }],
+        block_comments: vec![BlockComment::new("/*", "*/")],
+        strings: vec![
+            // Indented strings escape with '', e.g. ''${ is a literal ${
+            StringLiteral {
+                escape_sequences: ["'''", "''$", "''\\", "$${"].map(String::from).into(),
+                interpolation: Some("${".into()),
+                ..StringLiteral::verbatim("''", "''")
+            },
+            StringLiteral {
+                escape: Some('\\'),
+                escape_sequences: vec!["$${".into()],
+                interpolation: Some("${".into()),
+                ..StringLiteral::verbatim("\"", "\"")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// GraphQL descriptions are strings rather than comments, so are kept as schema metadata
+static GRAPHQL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "GraphQL",
+    id: "graphql",
+    aliases: ["gql"].into(),
+    file_extensions: ["graphql", "graphqls", "gql"].into(),
+    path_globs: vec![],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("#")],
+        block_comments: vec![],
+        // Descriptions are strings before a type, field, argument, enum value or directive
+        // definition. They are only removed with strip_docstrings
+        strings: vec![
+            StringLiteral {
+                escape_sequences: vec!["\\\"\"\"".into()],
+                documentation: true,
+                ..StringLiteral::verbatim("\"\"\"", "\"\"\"")
+            },
+            StringLiteral {
+                documentation: true,
+                ..StringLiteral::new("\"")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Erlang character literals such as $% and $" are matched whole, so they don't start a comment or
+// a string
+static ERLANG: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Erlang",
+    id: "erlang",
+    aliases: ["erl"].into(),
+    file_extensions: ["erl", "hrl", "escript"].into(),
+    path_globs: vec![
+        Pattern::from_str("**/rebar.config").unwrap(),
+        Pattern::from_str("**/*.app.src").unwrap(),
+    ],
+    doc_comment_prefixes: &["%% @doc", "%%% @doc"],
+    stripper: Stripper::Lexer(LexerDefinition {
+        line_comments: vec![LineComment::new("%")],
+        block_comments: vec![],
+        strings: vec![
+            StringLiteral::verbatim("$\\%", ""),
+            StringLiteral::verbatim("$\\\"", ""),
+            StringLiteral::verbatim("$\\'", ""),
+            StringLiteral::verbatim("$%", ""),
+            StringLiteral::verbatim("$\"", ""),
+            StringLiteral::verbatim("$'", ""),
+            StringLiteral::verbatim("\"\"\"", "\"\"\""),
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("\"")
+            },
+            StringLiteral {
+                multiline: true,
+                ..StringLiteral::new("'")
+            },
+        ],
+        dollar_quoted_strings: false,
+        trailing_commas: false,
+        heredocs: false,
+        quote_like_operators: false,
+        end_markers: vec![],
+    }),
+});
+
+// Copies files unchanged. Select it using a type hint for files that should ship as-is,
+// such as JSON, lockfiles and golden data
+static NULL: Lazy<LanguageDefinition> = Lazy::new(|| LanguageDefinition {
+    name: "Null",
+    id: "null",
+    aliases: ["none", "copy", "passthrough"].into(),
+    file_extensions: [].into(),
+    // CMake presets are strict JSON, which CMake rejects if it contains comments, so there is
+    // nothing to strip
+    path_globs: vec![
+        Pattern::from_str("**/CMakePresets.json").unwrap(),
+        Pattern::from_str("**/CMakeUserPresets.json").unwrap(),
+    ],
+    doc_comment_prefixes: &[],
+    stripper: Stripper::Null,
+});
 
 // All supported languages
-pub static LANGUAGES: [&LanguageDefinition; 29] = [
+pub static LANGUAGES: [&Lazy<LanguageDefinition>; 72] = [
     &RUST,
     &TYPESCRIPT,
     &TYPESCRIPT_REACT,
@@ -288,12 +1907,20 @@ pub static LANGUAGES: [&LanguageDefinition; 29] = [
     &OBJECTIVE_C,
     &JAVA,
     &HTML,
+    &VUE,
+    &SVELTE,
     &PHP,
     &LUA,
     &SWIFT,
     &YAML,
     &RUBY,
     &KOTLIN,
+    &GROOVY,
+    &SCALA,
+    &PERL,
+    &R,
+    &HASKELL,
+    &ELIXIR,
     &PROTO,
     &TOML,
     &C_SHARP,
@@ -304,4 +1931,86 @@ pub static LANGUAGES: [&LanguageDefinition; 29] = [
     &HCL,
     &MAKE,
     &INI,
+    &MARKDOWN,
+    &JUPYTER_NOTEBOOK,
+    &HASH_COMMENTS,
+    &C_STYLE_COMMENTS,
+    &PROPERTIES,
+    &GITCONFIG,
+    &EDITORCONFIG,
+    &NGINX,
+    &BATCH,
+    &GNU_ASSEMBLER,
+    &GNU_ASSEMBLER_X86,
+    &GNU_ASSEMBLER_ARM,
+    &NASM,
+    &PROTO_TEXT,
+    &THRIFT,
+    &FLATBUFFERS,
+    &CAPNP,
+    &JSONC,
+    &JSON5,
+    &SOLUTION,
+    &MODULE_DEFINITION,
+    &SYSTEMD,
+    &DESKTOP_ENTRY,
+    &SQL,
+    &POSTGRESQL,
+    &MYSQL,
+    &VERILOG,
+    &SYSTEM_VERILOG,
+    &VHDL,
+    &DART,
+    &ZIG,
+    &NIX,
+    &GRAPHQL,
+    &ERLANG,
+    &NULL,
 ];
+
+/// Languages registered at runtime, typically from the config file
+static REGISTERED_LANGUAGES: OnceCell<Vec<LanguageDefinition>> = OnceCell::new();
+
+/// Registers additional languages alongside the built-in languages
+///
+/// This may only be called once, and should be called before any files are processed
+///
+/// # Arguments
+/// * `languages` - The languages to register
+///
+/// # Return
+/// On failure, the languages that could not be registered
+pub fn register_languages(
+    languages: Vec<LanguageDefinition>,
+) -> Result<(), Vec<LanguageDefinition>> {
+    REGISTERED_LANGUAGES.set(languages)
+}
+
+/// Iterates over all languages, built-in languages first followed by registered languages
+pub fn all_languages() -> impl Iterator<Item = &'static LanguageDefinition> {
+    LANGUAGES
+        .iter()
+        .map(|&language| Lazy::force(language))
+        .chain(REGISTERED_LANGUAGES.get().into_iter().flatten())
+}
+
+/// Finds a language by name, identifier or alias
+///
+/// The comparison is case insensitive, so "Typescript with React", "typescript-react"
+/// and "TSX" all find the same language
+///
+/// # Arguments
+/// * `name` - The name, identifier or alias of the language
+///
+/// # Return
+/// The matching language, or None if no language matches
+pub fn find_language(name: &str) -> Option<&'static LanguageDefinition> {
+    all_languages().find(|language| {
+        language.name.eq_ignore_ascii_case(name)
+            || language.id.eq_ignore_ascii_case(name)
+            || language
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    })
+}
@@ -1,5 +1,10 @@
+pub mod config;
+pub mod docstrings;
 pub mod errors;
 pub mod languages;
 pub mod strip_core;
+pub mod strip_lexer;
+pub mod strip_notebook;
+pub mod strip_options;
 pub mod strip_process;
 pub mod type_hints;
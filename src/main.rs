@@ -1,8 +1,11 @@
 use std::path::{Path, PathBuf};
 
 use clap::{ArgAction, Parser};
+use ed_strip::config::load_config_file;
 use ed_strip::errors::{EdStripResult, StrippingError};
-use ed_strip::strip_process::{find_files, process_file};
+use ed_strip::languages::{all_languages, register_languages};
+use ed_strip::strip_options::StripOptions;
+use ed_strip::strip_process::{find_files, process_file, ProcessOutcome};
 use ed_strip::type_hints::{load_type_hints_file, TypeHints};
 use log::debug;
 use rayon::prelude::*;
@@ -12,12 +15,12 @@ use rayon::prelude::*;
 #[command(version, about, long_about = None)]
 struct Args {
     /// Directory to read from
-    #[arg(short, long)]
-    input_dir: PathBuf,
+    #[arg(short, long, required_unless_present = "list_languages")]
+    input_dir: Option<PathBuf>,
 
     /// Directory to output to
-    #[arg(short, long)]
-    output_dir: PathBuf,
+    #[arg(short, long, required_unless_present = "list_languages")]
+    output_dir: Option<PathBuf>,
 
     /// Glob to use. Should not be expanded by shell
     #[arg(short, long, default_value_t = String::from("**/*.*"))]
@@ -31,6 +34,10 @@ struct Args {
     #[arg(short = 't', long = "type-hints")]
     type_hints_path: Option<PathBuf>,
 
+    /// An optional JSON file containing additional languages
+    #[arg(short = 'c', long = "config")]
+    config_path: Option<PathBuf>,
+
     /// Increase verbosity to debug if specified once, or trace if specified twice
     #[arg(short = 'v', long = "verbose", action = ArgAction::Count)]
     verbose: u8,
@@ -38,19 +45,75 @@ struct Args {
     /// Decrease verbosity to warn if specified once, or error if specified twice. Overrides -v
     #[arg(short = 'q', long = "quiet", action = ArgAction::Count)]
     quiet: u8,
+
+    /// Also remove HTML comments from Markdown. By default only fenced code blocks are stripped
+    #[arg(long = "markdown-html-comments")]
+    markdown_html_comments: bool,
+
+    /// Also clear the outputs and execution counts of Jupyter notebook code cells
+    #[arg(long = "clear-notebook-outputs")]
+    clear_notebook_outputs: bool,
+
+    /// Also remove docstrings and documentation blocks, such as Python docstrings, Perl POD, Elixir @doc
+    /// and GraphQL descriptions
+    #[arg(long = "strip-docstrings")]
+    strip_docstrings: bool,
+
+    /// Also remove trailing commas from JSONC and JSON5, so JSONC output is strict JSON
+    #[arg(long = "remove-trailing-commas")]
+    remove_trailing_commas: bool,
+
+    /// Keep doc comments, such as Javadoc, Scaladoc and Rust /// comments
+    #[arg(long = "keep-doc-comments")]
+    keep_doc_comments: bool,
+
+    /// List the supported languages and exit
+    #[arg(long = "list-languages")]
+    list_languages: bool,
+}
+
+/// Joins a set of names into a sorted, comma separated list
+fn sorted_list<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    let mut items: Vec<&str> = items.collect();
+    items.sort_unstable();
+    items.join(", ")
+}
+
+/// Print the supported languages, along with the aliases, extensions and globs that select them
+fn list_languages() {
+    for language in all_languages() {
+        println!("{} ({})", language.name, language.id);
+        println!(
+            "  aliases:    {}",
+            sorted_list(language.aliases.iter().copied())
+        );
+        println!(
+            "  extensions: {}",
+            sorted_list(language.file_extensions.iter().copied())
+        );
+        println!(
+            "  globs:      {}",
+            sorted_list(language.path_globs.iter().map(|glob| glob.as_str()))
+        );
+    }
 }
 
 /// Output the stripping result for a single job
-/// Returns a tuple containing the number of jobs (1), and the number of successful jobs (1 or 0)
-fn report_result(result: Result<(), StrippingError>, path: &Path) -> (i32, i32) {
+/// Returns a tuple containing the number of jobs (1), the number of successful jobs (1 or 0),
+/// and the number of jobs copied unchanged (1 or 0)
+fn report_result(result: Result<ProcessOutcome, StrippingError>, path: &Path) -> (i32, i32, i32) {
     match &result {
-        Ok(()) => {
+        Ok(ProcessOutcome::Stripped) => {
             log::info!("{}: OK", path.to_string_lossy());
-            (1, 1)
+            (1, 1, 0)
+        }
+        Ok(ProcessOutcome::Copied) => {
+            log::info!("{}: Copied unchanged", path.to_string_lossy());
+            (1, 1, 1)
         }
         Err(e) => {
             log::warn!("{}: {}", path.to_string_lossy(), e);
-            (1, 0)
+            (1, 0, 0)
         }
     }
 }
@@ -74,11 +137,28 @@ fn main() -> EdStripResult<()> {
         env_logger::init();
     }
 
-    // Parse the arguments
+    // Load the config, registering any additional languages
+    if let Some(config_path) = args.config_path {
+        let config = load_config_file(&config_path)?;
+        let languages = config.language_definitions()?;
+        debug!("Registering {} languages from config", languages.len());
+        if register_languages(languages).is_err() {
+            log::warn!("Languages have already been registered");
+        }
+    } else {
+        debug!("No config specified");
+    }
 
-    let input_dir = std::path::absolute(args.input_dir)
+    if args.list_languages {
+        list_languages();
+        return Ok(());
+    }
+
+    // Parse the arguments
+    // clap ensures these are present unless listing languages
+    let input_dir = std::path::absolute(args.input_dir.unwrap())
         .map_err(|e: std::io::Error| -> StrippingError { e.into() })?;
-    let output_dir = std::path::absolute(args.output_dir)
+    let output_dir = std::path::absolute(args.output_dir.unwrap())
         .map_err(|e: std::io::Error| -> StrippingError { e.into() })?;
 
     // Load the type hints
@@ -89,6 +169,14 @@ fn main() -> EdStripResult<()> {
         Vec::new()
     };
 
+    let options = StripOptions {
+        markdown_html_comments: args.markdown_html_comments,
+        clear_notebook_outputs: args.clear_notebook_outputs,
+        strip_docstrings: args.strip_docstrings,
+        remove_trailing_commas: args.remove_trailing_commas,
+        keep_doc_comments: args.keep_doc_comments,
+    };
+
     // Find files
     let files = find_files(&input_dir, &args.glob)?;
 
@@ -99,24 +187,32 @@ fn main() -> EdStripResult<()> {
         .build_global()?;
 
     // Strip each file
-    let (total_jobs, passed_jobs) = files
+    let (total_jobs, passed_jobs, copied_jobs) = files
         .par_bridge()
         .map(|path| {
             match path {
                 Ok(path) => {
-                    let result = process_file(&input_dir, &output_dir, &type_hints, &path);
+                    let result =
+                        process_file(&input_dir, &output_dir, &type_hints, &options, &path);
                     report_result(result, &path)
                 }
                 Err(e) => {
                     // Error unwrapping path - probably permissions problem
                     log::warn!("Glob error: {}", e);
-                    (1, 0)
+                    (1, 0, 0)
                 }
             }
         })
-        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+        .reduce(|| (0, 0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2));
 
     log::info!("{}/{} jobs passed", passed_jobs, total_jobs);
+    if copied_jobs > 0 {
+        log::info!(
+            "{} jobs stripped, {} jobs copied unchanged",
+            passed_jobs - copied_jobs,
+            copied_jobs
+        );
+    }
     if passed_jobs != total_jobs {
         log::warn!("{} jobs failed", total_jobs - passed_jobs);
     }
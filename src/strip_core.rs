@@ -1,52 +1,251 @@
+use std::{collections::HashSet, ops::Range};
+
 use log::trace;
-use tree_sitter::{Tree, TreeCursor};
+use tree_sitter::{Node, Parser as TSParser, Tree, TreeCursor};
+
+use crate::{
+    errors::StrippingError,
+    languages::{
+        find_language, DocstringFinder, Injection, InjectionLanguage, LanguageDefinition, Stripper,
+    },
+    strip_lexer,
+    strip_notebook::strip_notebook,
+    strip_options::StripOptions,
+};
 
-use crate::languages::LanguageDefinition;
+/// A comment found in the source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The byte range of the comment
+    pub range: Range<usize>,
+
+    /// Text that must replace the comment to keep the source valid, e.g. pass
+    pub placeholder: Option<&'static str>,
+}
+
+impl From<Range<usize>> for Comment {
+    fn from(range: Range<usize>) -> Self {
+        Comment {
+            range,
+            placeholder: None,
+        }
+    }
+}
 
 /// Determines if the cursor lies on a comment
-fn is_comment(cursor: &TreeCursor<'_>, language_definition: &LanguageDefinition) -> bool {
-    language_definition
-        .comment_node_types
-        .contains(cursor.node().kind())
+fn is_comment(cursor: &TreeCursor<'_>, comment_node_types: &HashSet<&'static str>) -> bool {
+    comment_node_types.contains(cursor.node().kind())
 }
 
 /// Generates a string that can replace the comment
-/// This is guaranteed to contain the same number of newlines
-fn comment_replacement(cursor: &TreeCursor<'_>, source_code: &str) -> String {
-    let node = cursor.node();
-    let node_source = &source_code[node.byte_range()];
-    let newline_count = node_source.chars().filter(|c| *c == '\n').count();
+/// This is guaranteed to contain the same number of newlines, following the placeholder if any
+fn comment_replacement(comment: &str, placeholder: Option<&str>) -> String {
+    let newline_count = comment.chars().filter(|c| *c == '\n').count();
 
-    trace!("Stripping comment '{}'", node_source.trim_end());
-    "\n".repeat(newline_count)
+    trace!("Stripping comment '{}'", comment.trim_end());
+    placeholder.unwrap_or_default().to_string() + &"\n".repeat(newline_count)
 }
 
-/// Strips comments from the source code
-/// Input must contain \n newlines only
-pub fn strip_comments(
-    tree: &mut Tree,
-    language_definition: &LanguageDefinition,
+/// Finds the value of an attribute on the start tag of an HTML element
+///
+/// # Arguments
+/// * `node` - An HTML element, such as a script_element
+/// * `name` - The attribute name. This is case insensitive
+/// * `source_code` - The source code the node was parsed from
+fn attribute_value<'a>(node: &Node<'_>, name: &str, source_code: &'a str) -> Option<&'a str> {
+    let mut cursor = node.walk();
+    let start_tag = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "start_tag")?;
+
+    let mut cursor = start_tag.walk();
+    let attributes: Vec<Node<'_>> = start_tag
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "attribute")
+        .collect();
+
+    attributes.into_iter().find_map(|attribute| {
+        let mut cursor = attribute.walk();
+        let mut children = attribute.named_children(&mut cursor);
+        let attribute_name = children.next()?;
+        if !source_code[attribute_name.byte_range()].eq_ignore_ascii_case(name) {
+            return None;
+        }
+
+        // Attribute values may be bare or quoted. Valueless attributes have an empty value
+        let value = match children.next() {
+            Some(value) if value.kind() == "quoted_attribute_value" => value
+                .named_child(0)
+                .map(|value| &source_code[value.byte_range()])
+                .unwrap_or(""),
+            Some(value) => &source_code[value.byte_range()],
+            None => "",
+        };
+        Some(value.trim())
+    })
+}
+
+/// Source embedded in another language
+struct EmbeddedSource<'tree> {
+    /// The node holding the embedded source
+    node: Node<'tree>,
+
+    /// The ranges of the embedded source. Usually this is just the range of the node
+    ranges: Vec<tree_sitter::Range>,
+
+    /// The language to strip the embedded source with
+    language: &'static LanguageDefinition,
+
+    /// If true, the embedded source is parsed along with all other combined source
+    combined: bool,
+}
+
+/// Finds the ranges of a node, excluding any children of the given types
+fn content_ranges(node: &Node<'_>, excluded_node_types: &[&str]) -> Vec<tree_sitter::Range> {
+    let mut ranges: Vec<tree_sitter::Range> = Vec::new();
+    let mut start = (node.start_byte(), node.start_position());
+
+    let mut cursor = node.walk();
+    for child in node
+        .children(&mut cursor)
+        .filter(|child| excluded_node_types.contains(&child.kind()))
+    {
+        if start.0 < child.start_byte() {
+            ranges.push(tree_sitter::Range {
+                start_byte: start.0,
+                start_point: start.1,
+                end_byte: child.start_byte(),
+                end_point: child.start_position(),
+            });
+        }
+        start = (child.end_byte(), child.end_position());
+    }
+
+    if start.0 < node.end_byte() {
+        ranges.push(tree_sitter::Range {
+            start_byte: start.0,
+            start_point: start.1,
+            end_byte: node.end_byte(),
+            end_point: node.end_position(),
+        });
+    }
+    ranges
+}
+
+/// Finds the child of a node with the given type
+fn child_of_type<'tree>(node: &Node<'tree>, node_type: &str) -> Option<Node<'tree>> {
+    let mut cursor = node.walk();
+    let child = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == node_type);
+    child
+}
+
+/// Determines the language of source embedded in a node, if any
+///
+/// # Return
+/// The embedded source, if the node contains any in a known language
+fn embedded_source<'tree>(
+    node: &Node<'tree>,
+    injections: &[Injection],
     source_code: &str,
-) -> String {
+    options: &StripOptions,
+) -> Option<EmbeddedSource<'tree>> {
+    let injection = injections
+        .iter()
+        .find(|injection| injection.node_type == node.kind())?;
+
+    if let Some(enabled_by) = injection.enabled_by {
+        if !enabled_by(options) {
+            return None;
+        }
+    }
+
+    let content = match injection.content_node_type {
+        Some(content_node_type) => child_of_type(node, content_node_type)?,
+        None => *node,
+    };
+
+    let (language, name) = match &injection.language {
+        InjectionLanguage::Fixed(language) => (Some(*language), None),
+        InjectionLanguage::Attribute {
+            name,
+            default,
+            languages,
+        } => match attribute_value(node, name, source_code) {
+            Some(value) => (
+                languages.get(value.to_lowercase().as_str()).copied(),
+                Some(value),
+            ),
+            None => (*default, None),
+        },
+        InjectionLanguage::ChildText(node_type) => {
+            // Info strings may contain more than the language, e.g. rust,ignore or python title="a.py"
+            // R Markdown wraps them in braces, e.g. {r setup, echo=FALSE}
+            let name = child_of_type(node, node_type).and_then(|child| {
+                source_code[child.byte_range()]
+                    .split(|c: char| c.is_whitespace() || matches!(c, ',' | '{' | '}'))
+                    .find(|word| !word.is_empty())
+            });
+            (name.and_then(find_language), name)
+        }
+    };
+
+    if language.is_none() {
+        trace!(
+            "Not stripping embedded {} with unknown language '{}'",
+            injection.node_type,
+            name.unwrap_or_default()
+        );
+    }
+
+    language.map(|language| EmbeddedSource {
+        node: content,
+        ranges: content_ranges(&content, injection.excluded_node_types),
+        language,
+        combined: injection.combined,
+    })
+}
+
+/// Finds the comments in a tree
+///
+/// # Return
+/// A tuple containing the comments, and the source embedded in other languages
+fn find_tree_comments<'tree>(
+    tree: &'tree Tree,
+    comment_node_types: &HashSet<&'static str>,
+    separator_comment_types: &HashSet<&'static str>,
+    injections: &[Injection],
+    docstrings: Option<DocstringFinder>,
+    source_code: &str,
+    options: &StripOptions,
+) -> (Vec<Comment>, Vec<EmbeddedSource<'tree>>) {
     // First visit child nodes. We only need to visit the first?
     // If no child nodes, visit next siblings
     let mut cursor = tree.walk();
-    // As we replace code, the output will gradually get shorter
-    let mut truncate_offset = 0usize;
-    let mut result: String = source_code.into();
+    let mut comments: Vec<Comment> = Vec::new();
+    let mut embedded: Vec<EmbeddedSource<'tree>> = Vec::new();
+
+    // Docstrings are only found if they are being stripped
+    let docstrings = docstrings.filter(|_| options.strip_docstrings);
 
     loop {
-        if is_comment(&cursor, language_definition) {
-            let replacement = comment_replacement(&cursor, source_code);
-            let mut range = cursor.node().byte_range();
-
-            range.start -= truncate_offset;
-            range.end -= truncate_offset;
-            truncate_offset += range.len();
-            truncate_offset -= replacement.len();
-            result.replace_range(range, &replacement);
+        if is_comment(&cursor, comment_node_types) {
+            let node = cursor.node();
+            comments.push(Comment {
+                range: node.byte_range(),
+                placeholder: separator_placeholder(&node, separator_comment_types, source_code),
+            });
+        } else if let Some(docstring) =
+            docstrings.and_then(|finder| finder(&cursor.node(), source_code))
+        {
+            comments.push(docstring);
+        } else if let Some(source) =
+            embedded_source(&cursor.node(), injections, source_code, options)
+        {
+            embedded.push(source);
         }
-        // Visit children, unless we just nuked the node
+        // Visit children, unless we just found a comment, docstring or embedded source
         else if cursor.goto_first_child() {
             // Successfully went to child node, continue loop
             continue;
@@ -64,8 +263,307 @@ pub fn strip_comments(
         while !cursor.goto_next_sibling() {
             if !cursor.goto_parent() {
                 // Reached the root again. Terminate search
-                return result;
+                return (comments, embedded);
             }
         }
     }
 }
+
+/// Finds the placeholder for a comment that separates the tokens around it
+///
+/// Removing the comment would join the tokens, so it is replaced with a space, unless it is
+/// already next to whitespace
+fn separator_placeholder(
+    node: &Node<'_>,
+    separator_comment_types: &HashSet<&'static str>,
+    source_code: &str,
+) -> Option<&'static str> {
+    let before = source_code[..node.start_byte()].chars().next_back();
+    let after = source_code[node.end_byte()..].chars().next();
+    let separates = separator_comment_types.contains(node.kind())
+        && before.is_some_and(|c| !c.is_whitespace())
+        && after.is_some_and(|c| !c.is_whitespace());
+    separates.then_some(" ")
+}
+
+/// Determines if a comment starts with a doc comment prefix
+///
+/// A prefix ending in / or * does not match when the same character follows it, as //// and
+/// /*** are ordinary comments, often used as separators and banners. /**/ is an empty comment
+fn has_doc_comment_prefix(text: &str, prefix: &str) -> bool {
+    let Some(rest) = text.strip_prefix(prefix) else {
+        return false;
+    };
+
+    match prefix.chars().next_back() {
+        Some('/') => !rest.starts_with('/'),
+        Some('*') => !rest.starts_with(['*', '/']),
+        _ => true,
+    }
+}
+
+/// Excludes doc comments from the comments to remove, if doc comments are being kept
+fn exclude_doc_comments(
+    comments: &mut Vec<Comment>,
+    language_definition: &LanguageDefinition,
+    source_code: &str,
+    options: &StripOptions,
+) {
+    if !options.keep_doc_comments {
+        return;
+    }
+
+    comments.retain(|comment| {
+        let text = &source_code[comment.range.clone()];
+        !language_definition
+            .doc_comment_prefixes
+            .iter()
+            .any(|prefix| has_doc_comment_prefix(text, prefix))
+    });
+}
+
+/// Restricts a range to the included ranges of the language it was parsed from
+///
+/// Embedded source may span several included ranges, for example a script element in a PHP
+/// template that contains a PHP tag. Only the parts inside the included ranges are embedded source
+///
+/// # Arguments
+/// * `range` - The range of the embedded source
+/// * `included_ranges` - The included ranges of the parent language. If empty, the whole source
+fn intersect_ranges(
+    range: tree_sitter::Range,
+    included_ranges: &[tree_sitter::Range],
+) -> Vec<tree_sitter::Range> {
+    if included_ranges.is_empty() {
+        return vec![range];
+    }
+
+    included_ranges
+        .iter()
+        .filter(|included| {
+            included.start_byte < range.end_byte && range.start_byte < included.end_byte
+        })
+        .map(|included| {
+            let start = if included.start_byte > range.start_byte {
+                included
+            } else {
+                &range
+            };
+            let end = if included.end_byte < range.end_byte {
+                included
+            } else {
+                &range
+            };
+            tree_sitter::Range {
+                start_byte: start.start_byte,
+                start_point: start.start_point,
+                end_byte: end.end_byte,
+                end_point: end.end_point,
+            }
+        })
+        .collect()
+}
+
+/// Restricts comments to the included ranges of the language they were parsed from
+///
+/// A comment in embedded source may span text that belongs to the parent language, such as the
+/// block quote markers of a Markdown code block. Only the parts inside the included ranges are
+/// removed
+fn clip_comments(comments: Vec<Comment>, included_ranges: &[tree_sitter::Range]) -> Vec<Comment> {
+    if included_ranges.is_empty() {
+        return comments;
+    }
+
+    comments
+        .into_iter()
+        .flat_map(|comment| {
+            included_ranges
+                .iter()
+                .map(move |included| {
+                    comment.range.start.max(included.start_byte)
+                        ..comment.range.end.min(included.end_byte)
+                })
+                .filter(|clipped| clipped.start < clipped.end)
+                .enumerate()
+                .map(move |(index, range)| Comment {
+                    range,
+                    // The placeholder is only needed once
+                    placeholder: comment.placeholder.filter(|_| index == 0),
+                })
+        })
+        .collect()
+}
+
+/// Finds the comments in the source code
+///
+/// Source embedded in another language (such as a script element in HTML) is parsed using the
+/// included ranges of the embedded language, so all byte ranges refer to the original source
+///
+/// # Arguments
+/// * `language_definition` - The language to find comments in
+/// * `source_code` - The source code
+/// * `included_ranges` - The ranges of the source in this language. If empty, the whole source
+/// * `options` - Options controlling what is stripped
+///
+/// # Return
+/// On success, the comments in the source code
+pub fn find_comments(
+    language_definition: &LanguageDefinition,
+    source_code: &str,
+    included_ranges: &[tree_sitter::Range],
+    options: &StripOptions,
+) -> Result<Vec<Comment>, StrippingError> {
+    match &language_definition.stripper {
+        Stripper::TreeSitter {
+            language,
+            comment_node_types,
+            separator_comment_types,
+            injections,
+            docstrings,
+        } => {
+            // Create a parser for the language
+            let mut parser = TSParser::new();
+            parser.set_language(language)?;
+            parser.set_included_ranges(included_ranges)?;
+
+            // Parse the source
+            let tree = parser.parse(source_code, None).unwrap();
+
+            let (comments, embedded) = find_tree_comments(
+                &tree,
+                comment_node_types,
+                separator_comment_types,
+                injections,
+                *docstrings,
+                source_code,
+                options,
+            );
+            let mut comments = comments;
+            exclude_doc_comments(&mut comments, language_definition, source_code, options);
+            let mut comments = clip_comments(comments, included_ranges);
+
+            // Strip the embedded source. Combined source is collected and parsed together
+            let mut combined: Vec<(&'static LanguageDefinition, Vec<tree_sitter::Range>)> =
+                Vec::new();
+            for source in embedded {
+                let ranges: Vec<tree_sitter::Range> = source
+                    .ranges
+                    .into_iter()
+                    .flat_map(|range| intersect_ranges(range, included_ranges))
+                    .collect();
+                if ranges.is_empty() {
+                    continue;
+                }
+
+                if source.combined {
+                    match combined
+                        .iter_mut()
+                        .find(|(language, _)| std::ptr::eq(*language, source.language))
+                    {
+                        Some((_, combined_ranges)) => combined_ranges.extend(ranges),
+                        None => combined.push((source.language, ranges)),
+                    }
+                } else {
+                    trace!(
+                        "Stripping embedded {} as {}",
+                        source.node.kind(),
+                        source.language.name
+                    );
+                    comments.extend(find_comments(
+                        source.language,
+                        source_code,
+                        &ranges,
+                        options,
+                    )?);
+                }
+            }
+
+            for (language, ranges) in combined {
+                trace!(
+                    "Stripping {} embedded ranges as {}",
+                    ranges.len(),
+                    language.name
+                );
+                comments.extend(find_comments(language, source_code, &ranges, options)?);
+            }
+            Ok(comments)
+        }
+        Stripper::Lexer(lexer) => {
+            let mut ranges: Vec<Range<usize>> = included_ranges
+                .iter()
+                .map(|range| range.start_byte..range.end_byte)
+                .collect();
+            if ranges.is_empty() {
+                ranges.push(0..source_code.len());
+            }
+
+            let mut comments: Vec<Comment> = ranges
+                .into_iter()
+                .flat_map(|range| {
+                    let source = &source_code[range.clone()];
+                    let ranges = strip_lexer::scan(lexer, source);
+                    let mut comments = ranges.comments;
+                    if options.remove_trailing_commas {
+                        comments.extend(ranges.trailing_commas);
+                    }
+                    if options.strip_docstrings {
+                        comments.extend(ranges.documentation);
+                    }
+
+                    comments.into_iter().map(move |comment| {
+                        (comment.start + range.start..comment.end + range.start).into()
+                    })
+                })
+                .collect();
+            exclude_doc_comments(&mut comments, language_definition, source_code, options);
+            Ok(comments)
+        }
+        // Notebooks are rewritten by strip_comments rather than having comments removed in place
+        Stripper::Notebook | Stripper::Null => Ok(vec![]),
+    }
+}
+
+/// Removes comments from the source code
+///
+/// Each comment is replaced by the newlines it contained, so line numbers are unchanged.
+/// Comments with a placeholder have the placeholder inserted before the newlines
+///
+/// # Arguments
+/// * `source_code` - The source code
+/// * `comments` - The comments. Overlapping comments are ignored
+pub fn remove_comments(source_code: &str, mut comments: Vec<Comment>) -> String {
+    comments.sort_by_key(|comment| comment.range.start);
+
+    let mut result = String::with_capacity(source_code.len());
+    let mut offset = 0usize;
+    for comment in comments {
+        if comment.range.start < offset {
+            continue;
+        }
+
+        result.push_str(&source_code[offset..comment.range.start]);
+        result.push_str(&comment_replacement(
+            &source_code[comment.range.clone()],
+            comment.placeholder,
+        ));
+        offset = comment.range.end;
+    }
+    result.push_str(&source_code[offset..]);
+
+    result
+}
+
+/// Strips comments from the source code
+/// Input must contain \n newlines only
+pub fn strip_comments(
+    language_definition: &LanguageDefinition,
+    source_code: &str,
+    options: &StripOptions,
+) -> Result<String, StrippingError> {
+    if let Stripper::Notebook = language_definition.stripper {
+        return strip_notebook(source_code, options);
+    }
+
+    let comments = find_comments(language_definition, source_code, &[], options)?;
+    Ok(remove_comments(source_code, comments))
+}
@@ -0,0 +1,1005 @@
+//! A lexer based stripper for languages with simple comment syntax
+//!
+//! Many file formats (config files in particular) have no tree-sitter grammar, but have
+//! comment rules simple enough to describe with a handful of markers. This module strips
+//! comments from such files by scanning for string literals, line comments and block comments.
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use crate::strip_core::{remove_comments, Comment};
+
+/// A comment that runs until the end of the line
+#[derive(Deserialize, Debug, Clone)]
+pub struct LineComment {
+    /// The marker that starts the comment, e.g. #
+    pub start: String,
+
+    /// If true, the comment is only recognised if the marker is the first
+    /// non-whitespace text on the line
+    #[serde(default)]
+    pub line_start_only: bool,
+
+    /// If true, the marker and the not_followed_by words are matched case insensitively,
+    /// e.g. REM and rem
+    #[serde(default)]
+    pub ignore_case: bool,
+
+    /// If true, the marker must not be followed by a letter, digit or underscore,
+    /// so REM does not match REMARK
+    #[serde(default)]
+    pub whole_word: bool,
+
+    /// If true, the marker must be followed by whitespace, a control character or the end of
+    /// the source, as MySQL requires for --
+    #[serde(default)]
+    pub followed_by_whitespace: bool,
+
+    /// If true, the marker must follow whitespace or start the source, so the # in a Nix URI
+    /// such as https://example.com/a#b does not start a comment
+    #[serde(default)]
+    pub preceded_by_whitespace: bool,
+
+    /// Words that stop the marker being a comment when they follow it, ignoring spaces and tabs.
+    /// For example, define and include are preprocessor directives when they follow #
+    #[serde(default)]
+    pub not_followed_by: Vec<String>,
+}
+
+/// A comment delimited by start and end markers
+#[derive(Deserialize, Debug, Clone)]
+pub struct BlockComment {
+    /// The marker that starts the comment, e.g. /*
+    pub start: String,
+
+    /// The marker that ends the comment, e.g. */
+    pub end: String,
+
+    /// If true, block comments may be nested inside each other
+    #[serde(default)]
+    pub nested: bool,
+
+    /// If true, the comment is only recognised if the marker is the first
+    /// non-whitespace text on the line
+    #[serde(default)]
+    pub line_start_only: bool,
+
+    /// If true, the comment is documentation, such as Perl POD. Documentation is kept unless the
+    /// strip_docstrings option is set
+    #[serde(default)]
+    pub documentation: bool,
+
+    /// If true, the not_followed_by words are matched case insensitively
+    #[serde(default)]
+    pub ignore_case: bool,
+
+    /// Words that make the comment a directive when they follow the start marker, ignoring
+    /// spaces and tabs. Directives are kept. For example, /* synopsys translate_off */
+    #[serde(default)]
+    pub not_followed_by: Vec<String>,
+}
+
+/// A string literal. Comment markers inside strings are ignored
+#[derive(Deserialize, Debug, Clone)]
+pub struct StringLiteral {
+    /// The marker that starts the string, e.g. "
+    pub start: String,
+
+    /// The marker that ends the string, e.g. "
+    pub end: String,
+
+    /// The escape character, if any, e.g. \
+    #[serde(default)]
+    pub escape: Option<char>,
+
+    /// If false, an unterminated string ends at the end of the line
+    #[serde(default)]
+    pub multiline: bool,
+
+    /// Sequences that are skipped over inside the string, e.g. ''$ in Nix indented strings
+    /// These are checked before the end marker and the interpolation marker
+    #[serde(default)]
+    pub escape_sequences: Vec<String>,
+
+    /// The marker that starts an interpolated expression, e.g. ${. The expression runs to the
+    /// matching }, and may contain strings and comments of its own
+    #[serde(default)]
+    pub interpolation: Option<String>,
+
+    /// If true, the string is documentation when it starts a line, does not follow :, = or [, and
+    /// is followed by a name. GraphQL descriptions are such strings, as they precede the definition
+    /// they describe, while values follow a name or sit in a list. Documentation is kept unless the
+    /// strip_docstrings option is set
+    #[serde(default)]
+    pub documentation: bool,
+}
+
+/// Describes the comment syntax of a language
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct LexerDefinition {
+    /// The line comments of the language
+    #[serde(default)]
+    pub line_comments: Vec<LineComment>,
+
+    /// The block comments of the language
+    #[serde(default)]
+    pub block_comments: Vec<BlockComment>,
+
+    /// The string literals of the language
+    #[serde(default)]
+    pub strings: Vec<StringLiteral>,
+
+    /// If true, PostgreSQL style dollar-quoted strings such as $$...$$ and $tag$...$tag$ are
+    /// recognised
+    #[serde(default)]
+    pub dollar_quoted_strings: bool,
+
+    /// If true, the language allows trailing commas before ] and }, as JSON5 does
+    /// These are removed if the remove_trailing_commas option is set
+    #[serde(default)]
+    pub trailing_commas: bool,
+
+    /// If true, Perl style here documents such as <<"EOF" are recognised. The body of a here
+    /// document starts on the next line and runs to the line holding only the terminator
+    #[serde(default)]
+    pub heredocs: bool,
+
+    /// If true, Perl quote-like operators such as q{}, qw//, m##, s{}{} and tr/// are recognised
+    /// with any delimiter, as are regular expressions such as /.../ after =~, !~ and (
+    #[serde(default)]
+    pub quote_like_operators: bool,
+
+    /// Markers that end the code when they are alone on a line, e.g. __END__ in Perl.
+    /// Everything after them is kept unchanged
+    #[serde(default)]
+    pub end_markers: Vec<String>,
+}
+
+impl LineComment {
+    /// Creates a line comment that can start anywhere on a line
+    pub fn new(start: &str) -> Self {
+        LineComment {
+            start: start.into(),
+            line_start_only: false,
+            ignore_case: false,
+            whole_word: false,
+            followed_by_whitespace: false,
+            preceded_by_whitespace: false,
+            not_followed_by: vec![],
+        }
+    }
+
+    /// Creates a line comment that must be the first non-whitespace text on a line
+    pub fn line_start(start: &str) -> Self {
+        LineComment {
+            start: start.into(),
+            line_start_only: true,
+            ignore_case: false,
+            whole_word: false,
+            followed_by_whitespace: false,
+            preceded_by_whitespace: false,
+            not_followed_by: vec![],
+        }
+    }
+
+    /// Determines if the source starts with this comment's marker
+    fn matches(&self, source: &str) -> bool {
+        let matched = match source.get(..self.start.len()) {
+            Some(marker) if self.ignore_case => marker.eq_ignore_ascii_case(&self.start),
+            Some(marker) => marker == self.start,
+            None => false,
+        };
+
+        if !matched {
+            return false;
+        }
+
+        let rest = &source[self.start.len()..];
+        if self.whole_word && rest.starts_with(is_word_char) {
+            return false;
+        }
+
+        if self.followed_by_whitespace
+            && !rest.is_empty()
+            && !rest.starts_with(|c: char| c.is_whitespace() || c.is_control())
+        {
+            return false;
+        }
+
+        !followed_by_any(rest, &self.not_followed_by, self.ignore_case)
+    }
+}
+
+/// Determines if a character is part of a word
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Determines if the source starts with one of the words, ignoring leading spaces and tabs
+fn followed_by_any(source: &str, words: &[String], ignore_case: bool) -> bool {
+    let next_word = source.trim_start_matches([' ', '\t']);
+    words.iter().any(|word| {
+        let matched = match next_word.get(..word.len()) {
+            Some(prefix) if ignore_case => prefix.eq_ignore_ascii_case(word),
+            Some(prefix) => prefix == word,
+            None => false,
+        };
+        matched && !next_word[word.len()..].starts_with(is_word_char)
+    })
+}
+
+impl BlockComment {
+    /// Creates a block comment
+    pub fn new(start: &str, end: &str) -> Self {
+        BlockComment {
+            start: start.into(),
+            end: end.into(),
+            nested: false,
+            line_start_only: false,
+            documentation: false,
+            ignore_case: false,
+            not_followed_by: vec![],
+        }
+    }
+
+    /// Determines if the source starts with a directive, rather than an ordinary comment
+    fn is_directive(&self, source: &str) -> bool {
+        followed_by_any(
+            &source[self.start.len()..],
+            &self.not_followed_by,
+            self.ignore_case,
+        )
+    }
+}
+
+impl StringLiteral {
+    /// Creates a single line string literal with \ escapes
+    pub fn new(delimiter: &str) -> Self {
+        StringLiteral {
+            start: delimiter.into(),
+            end: delimiter.into(),
+            escape: Some('\\'),
+            multiline: false,
+            escape_sequences: vec![],
+            interpolation: None,
+            documentation: false,
+        }
+    }
+
+    /// Creates a multiline string literal without escapes
+    pub fn verbatim(start: &str, end: &str) -> Self {
+        StringLiteral {
+            start: start.into(),
+            end: end.into(),
+            escape: None,
+            multiline: true,
+            escape_sequences: vec![],
+            interpolation: None,
+            documentation: false,
+        }
+    }
+}
+
+/// The Perl quote-like operators, and the number of delimited parts each takes.
+/// Longer names come first, so qq is not mistaken for q
+const QUOTE_LIKE_OPERATORS: [(&str, usize); 9] = [
+    ("qq", 1),
+    ("qw", 1),
+    ("qr", 1),
+    ("qx", 1),
+    ("q", 1),
+    ("m", 1),
+    ("tr", 2),
+    ("s", 2),
+    ("y", 2),
+];
+
+/// The tokens after which / starts a regular expression in Perl, rather than a division
+const REGEX_AFTER_TOKENS: [&str; 10] = ["=~", "!~", "(", ",", "{", ";", "!", "=", "&&", "||"];
+
+/// The words after which / starts a regular expression in Perl, rather than a division
+const REGEX_AFTER_WORDS: [&str; 8] = [
+    "split", "grep", "if", "unless", "and", "or", "not", "return",
+];
+
+/// Finds the length of a delimited part of a quote-like operator, such as {a} or /a/
+///
+/// Bracket delimiters nest, and \ escapes the next character
+///
+/// # Return
+/// The length of the part, including both delimiters. An unterminated part runs to the end of
+/// the source
+fn delimited_length(source: &str) -> usize {
+    let mut chars = source.char_indices();
+    let Some((_, open)) = chars.next() else {
+        return 0;
+    };
+    let close = match open {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        '<' => '>',
+        c => c,
+    };
+
+    let mut depth = 1usize;
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return index + c.len_utf8();
+            }
+        } else if c == open {
+            depth += 1;
+        }
+    }
+
+    source.len()
+}
+
+/// Determines if a character can delimit a quote-like operator. Closing brackets and characters
+/// that follow a bareword, such as in $hash{s} or (q, y), cannot
+fn is_quote_delimiter(c: char) -> bool {
+    !c.is_whitespace() && !is_word_char(c) && !",;)]}>".contains(c)
+}
+
+/// Finds the length of a Perl quote-like operator, such as q{...} or s/.../.../
+///
+/// # Return
+/// The length of the operator and its delimited parts, or None if the source at the offset does
+/// not start with one
+fn quote_like_length(source_code: &str, offset: usize) -> Option<usize> {
+    // Variables, method calls and package names such as $s, ->y and Foo::q are not operators
+    let before = &source_code[..offset];
+    if before.ends_with(|c: char| is_word_char(c) || "$@%&*:".contains(c)) || before.ends_with("->")
+    {
+        return None;
+    }
+
+    let rest = &source_code[offset..];
+    let (name, parts) = QUOTE_LIKE_OPERATORS
+        .iter()
+        .find(|(name, _)| rest.starts_with(name))?;
+    let body = &rest[name.len()..];
+    let delimiter = body.chars().next()?;
+    if !is_quote_delimiter(delimiter) || body.starts_with("=>") {
+        return None;
+    }
+
+    let mut length = delimited_length(body);
+    if *parts == 2 && length < body.len() {
+        if matches!(delimiter, '(' | '[' | '{' | '<') {
+            // The second part has its own delimiters, and may follow whitespace, e.g. s{a} {b}
+            let after = &body[length..];
+            let second = after.trim_start();
+            if second.starts_with(is_quote_delimiter) {
+                length += after.len() - second.len() + delimited_length(second);
+            }
+        } else {
+            // The closing delimiter of the first part opens the second, e.g. s/a/b/
+            let second = length - delimiter.len_utf8();
+            length = second + delimited_length(&body[second..]);
+        }
+    }
+
+    Some(name.len() + length)
+}
+
+/// Finds the length of a Perl regular expression match such as /.../, which is recognised after
+/// an operator or keyword where a division cannot appear
+///
+/// # Return
+/// The length of the match, or None if the source at the offset does not start with one
+fn regex_length(source_code: &str, offset: usize) -> Option<usize> {
+    let rest = &source_code[offset..];
+    if !rest.starts_with('/') {
+        return None;
+    }
+
+    let before = source_code[..offset].trim_end();
+    let after_word = REGEX_AFTER_WORDS.iter().any(|word| {
+        before.ends_with(word)
+            && !before[..before.len() - word.len()]
+                .ends_with(|c: char| is_word_char(c) || "$@%&".contains(c))
+    });
+    if !after_word
+        && !REGEX_AFTER_TOKENS
+            .iter()
+            .any(|token| before.ends_with(token))
+    {
+        return None;
+    }
+
+    // Character classes may contain /. A match that reaches the end of the line is not a regex
+    let mut in_class = false;
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return None,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return Some(index + 1),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Finds the length of a dollar-quoted string, including the delimiters
+///
+/// # Return
+/// The length of the string, or None if the source does not start with a dollar quote
+fn dollar_quoted_length(source: &str) -> Option<usize> {
+    // The tag is empty or an identifier, e.g. $$ or $body$. $1 is a parameter, not a tag
+    let tag_length = source.strip_prefix('$')?.find('$')? + 2;
+    let tag = &source[..tag_length];
+    let valid_tag = tag[1..tag_length - 1]
+        .chars()
+        .enumerate()
+        .all(|(index, c)| c == '_' || c.is_alphabetic() || (index > 0 && c.is_ascii_digit()));
+    if !valid_tag {
+        return None;
+    }
+
+    // Unterminated strings run to the end of the file
+    Some(
+        source[tag_length..]
+            .find(tag)
+            .map_or(source.len(), |end| tag_length + end + tag_length),
+    )
+}
+
+/// A here document whose body has not been reached yet
+struct Heredoc {
+    /// The line that ends the here document
+    terminator: String,
+
+    /// If true, the terminator may be indented, as with <<~EOF
+    indented: bool,
+}
+
+/// Parses the start of a here document, e.g. <<"EOF", <<'EOF', <<EOF or <<~EOF
+///
+/// # Return
+/// The length of the start marker and the here document, or None if the source does not start
+/// with one. << followed by anything else is a shift operator
+fn heredoc_start(source: &str) -> Option<(usize, Heredoc)> {
+    let after_marker = source.strip_prefix("<<")?;
+    let (indented, tag) = match after_marker.strip_prefix('~') {
+        Some(tag) => (true, tag),
+        None => (false, after_marker),
+    };
+
+    let (terminator, length) = match tag.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = tag[1..].find([quote, '\n'])? + 1;
+            if !tag[end..].starts_with(quote) {
+                return None;
+            }
+            (&tag[1..end], end + 1)
+        }
+        c if c.is_alphabetic() || c == '_' => {
+            let end = tag.find(|c| !is_word_char(c)).unwrap_or(tag.len());
+            (&tag[..end], end)
+        }
+        _ => return None,
+    };
+
+    let heredoc = Heredoc {
+        terminator: terminator.into(),
+        indented,
+    };
+    Some((source.len() - tag.len() + length, heredoc))
+}
+
+/// Finds the end of here document bodies, which follow each other from the start of a line
+///
+/// # Return
+/// The byte offset after the last terminator line, or the end of the source if a terminator is
+/// missing
+fn heredoc_bodies_end(source_code: &str, mut offset: usize, heredocs: &[Heredoc]) -> usize {
+    for heredoc in heredocs {
+        loop {
+            if offset >= source_code.len() {
+                return source_code.len();
+            }
+
+            let rest = &source_code[offset..];
+            let line_length = rest.find('\n').map_or(rest.len(), |end| end + 1);
+            let line = rest[..line_length].trim_end_matches(['\r', '\n']);
+            offset += line_length;
+            let line = if heredoc.indented {
+                line.trim_start()
+            } else {
+                line
+            };
+            if line == heredoc.terminator {
+                break;
+            }
+        }
+    }
+
+    offset
+}
+
+/// Determines if the source starts with an end marker alone on its line
+fn starts_with_end_marker(lexer: &LexerDefinition, source: &str) -> bool {
+    lexer.end_markers.iter().any(|marker| {
+        source.starts_with(marker.as_str())
+            && source[marker.len()..]
+                .trim_start_matches([' ', '\t', '\r'])
+                .chars()
+                .next()
+                .is_none_or(|c| c == '\n')
+    })
+}
+
+/// Finds the length of a block comment, including the delimiters
+fn block_comment_length(comment: &BlockComment, source: &str) -> usize {
+    let mut depth = 0usize;
+    let mut offset = 0usize;
+    while offset < source.len() {
+        let rest = &source[offset..];
+        if depth > 0 && rest.starts_with(&comment.end) {
+            depth -= 1;
+            offset += comment.end.len();
+            if depth == 0 {
+                return offset;
+            }
+        } else if (depth == 0 || comment.nested) && rest.starts_with(&comment.start) {
+            depth += 1;
+            offset += comment.start.len();
+        } else {
+            offset += rest.chars().next().unwrap().len_utf8();
+        }
+    }
+
+    // Unterminated comments run to the end of the file
+    source.len()
+}
+
+/// Finds the length of a line comment, excluding the line ending
+fn line_comment_length(source: &str) -> usize {
+    source.find(['\r', '\n']).unwrap_or(source.len())
+}
+
+/// The byte ranges found by scanning source code
+#[derive(Debug, Default)]
+pub struct LexedRanges {
+    /// The comments
+    pub comments: Vec<Range<usize>>,
+
+    /// The trailing commas, if the language allows them
+    pub trailing_commas: Vec<Range<usize>>,
+
+    /// The documentation, such as Perl POD and GraphQL descriptions
+    pub documentation: Vec<Range<usize>>,
+}
+
+/// Scans source code for comments, trailing commas and documentation
+struct Scanner<'a> {
+    /// The comment syntax of the language
+    lexer: &'a LexerDefinition,
+
+    /// The source code being scanned
+    source_code: &'a str,
+
+    /// The byte ranges found so far
+    ranges: LexedRanges,
+}
+
+impl Scanner<'_> {
+    /// Scans code, starting at the offset
+    ///
+    /// # Arguments
+    /// * `offset` - The byte offset to start scanning from
+    /// * `interpolation` - If true, the code is an interpolated expression inside a string, and
+    ///   scanning stops after the } that closes it
+    ///
+    /// # Return
+    /// The byte offset scanning stopped at
+    fn scan_code(&mut self, mut offset: usize, interpolation: bool) -> usize {
+        let lexer = self.lexer;
+        let source_code = self.source_code;
+        let mut line_start = !interpolation;
+
+        // The last comma, if only whitespace and comments have followed it
+        let mut comma: Option<usize> = None;
+
+        // The depth of braces opened inside an interpolated expression
+        let mut depth = 0usize;
+
+        // Here documents whose bodies start on the next line
+        let mut heredocs: Vec<Heredoc> = vec![];
+
+        while offset < source_code.len() {
+            let rest = &source_code[offset..];
+            if line_start && starts_with_end_marker(lexer, rest) {
+                return source_code.len();
+            }
+
+            if lexer.heredocs {
+                if let Some((length, heredoc)) = heredoc_start(rest) {
+                    heredocs.push(heredoc);
+                    offset += length;
+                    line_start = false;
+                    comma = None;
+                    continue;
+                }
+            }
+
+            if lexer.quote_like_operators {
+                if let Some(length) = quote_like_length(source_code, offset)
+                    .or_else(|| regex_length(source_code, offset))
+                {
+                    offset += length;
+                    line_start = false;
+                    comma = None;
+                    continue;
+                }
+            }
+
+            // A $ inside an identifier, such as a$b$, does not start a dollar quote
+            let in_identifier = || {
+                source_code[..offset]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$')
+            };
+            if lexer.dollar_quoted_strings && rest.starts_with('$') && !in_identifier() {
+                if let Some(length) = dollar_quoted_length(rest) {
+                    offset += length;
+                    line_start = false;
+                    comma = None;
+                    continue;
+                }
+            }
+
+            if let Some(string) = lexer.strings.iter().find(|s| rest.starts_with(&s.start)) {
+                let end = self.scan_string(string, offset);
+                if string.documentation && line_start && self.is_description(offset, end) {
+                    self.ranges.documentation.push(offset..end);
+                }
+                offset = end;
+                line_start = false;
+                comma = None;
+                continue;
+            }
+
+            if let Some(comment) = lexer
+                .block_comments
+                .iter()
+                .find(|c| rest.starts_with(&c.start) && (line_start || !c.line_start_only))
+            {
+                // Directives are skipped whole, so markers inside them are not mistaken for comments
+                let length = block_comment_length(comment, rest);
+                if comment.documentation {
+                    self.ranges.documentation.push(offset..offset + length);
+                } else if !comment.is_directive(rest) {
+                    self.ranges.comments.push(offset..offset + length);
+                }
+                offset += length;
+                continue;
+            }
+
+            let after_whitespace = || {
+                source_code[..offset]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+            };
+            if lexer.line_comments.iter().any(|c| {
+                c.matches(rest)
+                    && (line_start || !c.line_start_only)
+                    && (!c.preceded_by_whitespace || after_whitespace())
+            }) {
+                let length = line_comment_length(rest);
+                self.ranges.comments.push(offset..offset + length);
+                offset += length;
+                continue;
+            }
+
+            let c = rest.chars().next().unwrap();
+            offset += c.len_utf8();
+            match c {
+                ',' => comma = Some(offset - 1),
+                '}' if interpolation && depth == 0 => return offset,
+                ']' | '}' => {
+                    if let Some(comma) = comma.take().filter(|_| lexer.trailing_commas) {
+                        self.ranges.trailing_commas.push(comma..comma + 1);
+                    }
+                    if c == '}' {
+                        depth = depth.saturating_sub(1);
+                    }
+                }
+                '{' => {
+                    depth += 1;
+                    comma = None;
+                }
+                c if c.is_whitespace() => {}
+                _ => comma = None,
+            }
+            if c == '\n' && !heredocs.is_empty() {
+                offset = heredoc_bodies_end(source_code, offset, &heredocs);
+                heredocs.clear();
+            }
+            line_start = c == '\n' || (line_start && c.is_whitespace());
+        }
+
+        source_code.len()
+    }
+
+    /// Determines if a string that starts a line describes the definition that follows it
+    ///
+    /// # Arguments
+    /// * `start` - The byte offset of the start marker
+    /// * `end` - The byte offset after the end marker
+    fn is_description(&self, start: usize, end: usize) -> bool {
+        let before = self.source_code[..start].trim_end();
+        if before.ends_with([':', '=', '[']) {
+            return false;
+        }
+
+        // Skip any whitespace and line comments before the next token
+        let mut after = self.source_code[end..].trim_start();
+        while self.lexer.line_comments.iter().any(|c| c.matches(after)) {
+            after = after[line_comment_length(after)..].trim_start();
+        }
+        after.starts_with(|c: char| c.is_alphabetic() || c == '_')
+    }
+
+    /// Scans a string literal, including any interpolated expressions inside it
+    ///
+    /// # Arguments
+    /// * `string` - The string literal
+    /// * `start` - The byte offset of the start marker
+    ///
+    /// # Return
+    /// The byte offset after the end marker. An unterminated single line string ends before the
+    /// newline
+    fn scan_string(&mut self, string: &StringLiteral, start: usize) -> usize {
+        let source_code = self.source_code;
+        let mut offset = start + string.start.len();
+        while offset < source_code.len() {
+            let rest = &source_code[offset..];
+            if let Some(sequence) = string
+                .escape_sequences
+                .iter()
+                .find(|sequence| rest.starts_with(sequence.as_str()))
+            {
+                offset += sequence.len();
+                continue;
+            }
+
+            if rest.starts_with(&string.end) {
+                return offset + string.end.len();
+            }
+
+            if let Some(interpolation) = &string.interpolation {
+                if rest.starts_with(interpolation.as_str()) {
+                    offset = self.scan_code(offset + interpolation.len(), true);
+                    continue;
+                }
+            }
+
+            let c = rest.chars().next().unwrap();
+            if !string.multiline && c == '\n' {
+                // Unterminated string. Leave the newline for the caller
+                return offset;
+            }
+            offset += c.len_utf8();
+
+            // Skip the escaped character
+            if Some(c) == string.escape {
+                if let Some(escaped) = source_code[offset..].chars().next() {
+                    if string.multiline || escaped != '\n' {
+                        offset += escaped.len_utf8();
+                    }
+                }
+            }
+        }
+
+        source_code.len()
+    }
+}
+
+/// Scans the source code for comments, trailing commas and documentation in a single pass
+///
+/// # Return
+/// The byte ranges found. The caller chooses which of them to remove
+pub fn scan(lexer: &LexerDefinition, source_code: &str) -> LexedRanges {
+    let mut scanner = Scanner {
+        lexer,
+        source_code,
+        ranges: LexedRanges::default(),
+    };
+    scanner.scan_code(0, false);
+
+    scanner.ranges
+}
+
+/// Finds the comments in the source code
+///
+/// # Return
+/// The byte ranges of the comments
+pub fn find_comments(lexer: &LexerDefinition, source_code: &str) -> Vec<Range<usize>> {
+    scan(lexer, source_code).comments
+}
+
+/// Strips comments from the source code
+///
+/// Comments are replaced with the newlines they contained, so line numbers are unchanged
+pub fn strip_comments(lexer: &LexerDefinition, source_code: &str) -> String {
+    let comments = find_comments(lexer, source_code)
+        .into_iter()
+        .map(Comment::from)
+        .collect();
+    remove_comments(source_code, comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_lexer() -> LexerDefinition {
+        LexerDefinition {
+            line_comments: vec![LineComment::new("#")],
+            block_comments: vec![],
+            strings: vec![StringLiteral::new("\""), StringLiteral::new("'")],
+            dollar_quoted_strings: false,
+            trailing_commas: false,
+            heredocs: false,
+            quote_like_operators: false,
+            end_markers: vec![],
+        }
+    }
+
+    fn c_style_lexer() -> LexerDefinition {
+        LexerDefinition {
+            line_comments: vec![LineComment::new("//")],
+            block_comments: vec![BlockComment::new("/*", "*/")],
+            strings: vec![StringLiteral::new("\"")],
+            dollar_quoted_strings: false,
+            trailing_commas: false,
+            heredocs: false,
+            quote_like_operators: false,
+            end_markers: vec![],
+        }
+    }
+
+    /// GIVEN Source containing line comments
+    /// WHEN strip_comments is called
+    /// THEN the comments are removed but line endings remain
+    #[test]
+    fn lexer_strips_line_comments() {
+        let result = strip_comments(&hash_lexer(), "# header\r\nkey = value # trailing\n");
+        assert_eq!(result, "\r\nkey = value \n");
+    }
+
+    /// GIVEN Source containing comment markers inside strings
+    /// WHEN strip_comments is called
+    /// THEN the strings are unchanged
+    #[test]
+    fn lexer_ignores_markers_in_strings() {
+        let result = strip_comments(&hash_lexer(), "a = \"#not\\\" # comment\" # comment\n");
+        assert_eq!(result, "a = \"#not\\\" # comment\" \n");
+
+        let result = strip_comments(&hash_lexer(), "a = 'don't # comment\nb = 1\n");
+        assert_eq!(result, "a = 'don't \nb = 1\n");
+    }
+
+    /// GIVEN Source containing block comments
+    /// WHEN strip_comments is called
+    /// THEN the comments are replaced by the newlines they contained
+    #[test]
+    fn lexer_strips_block_comments() {
+        let result = strip_comments(&c_style_lexer(), "a /* one\ntwo */ b // c\n\"/*\"\n");
+        assert_eq!(result, "a \n b \n\"/*\"\n");
+    }
+
+    /// GIVEN A lexer with nested block comments
+    /// WHEN strip_comments is called
+    /// THEN the whole nested comment is removed
+    #[test]
+    fn lexer_strips_nested_block_comments() {
+        let mut lexer = c_style_lexer();
+        lexer.block_comments[0].nested = true;
+
+        let result = strip_comments(&lexer, "a /* outer /* inner */ still */ b\n");
+        assert_eq!(result, "a  b\n");
+    }
+
+    /// GIVEN A lexer with comments only at the start of the line
+    /// WHEN strip_comments is called
+    /// THEN comment markers later in the line are kept
+    #[test]
+    fn lexer_line_start_only_comments() {
+        let lexer = LexerDefinition {
+            line_comments: vec![LineComment::line_start("#")],
+            ..Default::default()
+        };
+
+        let result = strip_comments(&lexer, "  # comment\nkey=a#b\n");
+        assert_eq!(result, "  \nkey=a#b\n");
+    }
+
+    /// GIVEN A lexer with dollar-quoted strings
+    /// WHEN strip_comments is called
+    /// THEN comment markers inside dollar-quoted strings are kept, but parameters and
+    /// identifiers containing $ do not start a string
+    #[test]
+    fn lexer_ignores_markers_in_dollar_quoted_strings() {
+        let lexer = LexerDefinition {
+            line_comments: vec![LineComment::new("--")],
+            dollar_quoted_strings: true,
+            trailing_commas: false,
+            heredocs: false,
+            quote_like_operators: false,
+            end_markers: vec![],
+            ..Default::default()
+        };
+
+        let result = strip_comments(
+            &lexer,
+            "$$ -- a $$ -- b\n$body$ $$ -- c $body$ -- d\n$1 -- e $2\na$b$ -- f\n",
+        );
+        assert_eq!(result, "$$ -- a $$ \n$body$ $$ -- c $body$ \n$1 \na$b$ \n");
+    }
+
+    /// GIVEN A lexer that allows trailing commas
+    /// WHEN scan is called
+    /// THEN commas followed only by whitespace and comments before ] or } are found
+    #[test]
+    fn lexer_finds_trailing_commas() {
+        let lexer = LexerDefinition {
+            trailing_commas: true,
+            ..c_style_lexer()
+        };
+
+        let source = "{\"a\": [1, 2,], \",}\": 3, // c\n}";
+        let commas: Vec<usize> = scan(&lexer, source)
+            .trailing_commas
+            .into_iter()
+            .map(|comma| comma.start)
+            .collect();
+        assert_eq!(commas, vec![11, 22]);
+
+        assert!(scan(&c_style_lexer(), source).trailing_commas.is_empty());
+    }
+
+    /// GIVEN A lexer with block comments that are directives when followed by certain words
+    /// WHEN strip_comments is called
+    /// THEN the directives are kept whole, including any comment markers inside them
+    #[test]
+    fn lexer_keeps_block_comment_directives() {
+        let lexer = LexerDefinition {
+            block_comments: vec![BlockComment {
+                not_followed_by: vec!["pragma".into()],
+                ..BlockComment::new("/*", "*/")
+            }],
+            ..c_style_lexer()
+        };
+
+        let result = strip_comments(&lexer, "/* pragma a // b */ c /* pragmatic */ // d\n");
+        assert_eq!(result, "/* pragma a // b */ c  \n");
+    }
+
+    /// GIVEN A lexer with here documents, and source with several here documents on one line
+    /// WHEN strip_comments is called
+    /// THEN the bodies are kept, and << followed by anything else is not a here document
+    #[test]
+    fn lexer_skips_heredocs() {
+        let mut lexer = hash_lexer();
+        lexer.heredocs = true;
+
+        let source = "f(<<'A', << 2, <<\"B\"); # c\n# a\nA\n# b\nB\nx # c\n";
+        let result = strip_comments(&lexer, source);
+        assert_eq!(result, source.replace(" # c", " "));
+    }
+}
@@ -0,0 +1,290 @@
+//! Strips comments from Jupyter notebooks
+//!
+//! A notebook is a JSON document holding a list of cells. The source of each code cell is
+//! stripped using the language named in the notebook's kernel metadata. Rather than serializing
+//! the whole document again, only the JSON values that change are rewritten, so the key order and
+//! formatting of the notebook are preserved and diffs remain readable.
+use std::ops::Range;
+
+use log::debug;
+use tree_sitter::{Node, Parser as TSParser};
+
+use crate::{
+    errors::StrippingError,
+    languages::{find_language, LanguageDefinition},
+    strip_core::strip_comments,
+    strip_options::StripOptions,
+};
+
+/// A replacement for part of the notebook
+type Edit = (Range<usize>, String);
+
+/// Decodes a JSON string node
+fn decode_string(node: &Node<'_>, source_code: &str) -> Result<String, StrippingError> {
+    serde_json::from_str(&source_code[node.byte_range()])
+        .map_err(|e| StrippingError::InvalidNotebook(e.to_string()))
+}
+
+/// Finds the value of a key in a JSON object
+fn object_value<'tree>(object: &Node<'tree>, key: &str, source_code: &str) -> Option<Node<'tree>> {
+    if object.kind() != "object" {
+        return None;
+    }
+
+    let mut cursor = object.walk();
+    let value = object
+        .named_children(&mut cursor)
+        .filter(|pair| pair.kind() == "pair")
+        .find(|pair| {
+            pair.child_by_field_name("key")
+                .and_then(|name| decode_string(&name, source_code).ok())
+                .is_some_and(|name| name == key)
+        })
+        .and_then(|pair| pair.child_by_field_name("value"));
+    value
+}
+
+/// Finds a string nested inside JSON objects, e.g. metadata.kernelspec.language
+fn nested_string(object: &Node<'_>, path: &[&str], source_code: &str) -> Option<String> {
+    let value = path
+        .iter()
+        .try_fold(*object, |node, key| object_value(&node, key, source_code))?;
+    decode_string(&value, source_code).ok()
+}
+
+/// Finds the language of the notebook's code cells from the kernel metadata
+fn notebook_language(
+    notebook: &Node<'_>,
+    source_code: &str,
+) -> Option<&'static LanguageDefinition> {
+    let name = [
+        ["metadata", "kernelspec", "language"],
+        ["metadata", "language_info", "name"],
+    ]
+    .iter()
+    .find_map(|path| nested_string(notebook, path, source_code))?;
+
+    let language = find_language(&name);
+    if language.is_none() {
+        debug!("Not stripping code cells with unknown language '{}'", name);
+    }
+    language
+}
+
+/// Strips the source of a code cell
+///
+/// The source is either a single string, or a list of strings each holding one line. Stripping
+/// preserves newlines, so each string keeps its lines and only the strings that change are edited
+fn strip_cell_source(
+    cell_source: &Node<'_>,
+    language: &LanguageDefinition,
+    source_code: &str,
+    options: &StripOptions,
+    edits: &mut Vec<Edit>,
+) -> Result<(), StrippingError> {
+    let lines: Vec<Node<'_>> = match cell_source.kind() {
+        "string" => vec![*cell_source],
+        "array" => {
+            let mut cursor = cell_source.walk();
+            let lines = cell_source
+                .named_children(&mut cursor)
+                .filter(|line| line.kind() == "string")
+                .collect();
+            lines
+        }
+        _ => return Ok(()),
+    };
+    let decoded: Vec<String> = lines
+        .iter()
+        .map(|line| decode_string(line, source_code))
+        .collect::<Result<_, _>>()?;
+
+    let stripped = strip_comments(language, &decoded.concat(), options)?;
+
+    let mut rest = stripped.as_str();
+    for (index, (line, original)) in lines.iter().zip(decoded.iter()).enumerate() {
+        let length = match original.matches('\n').count() {
+            _ if index + 1 == lines.len() => rest.len(),
+            0 => 0,
+            newlines => rest
+                .match_indices('\n')
+                .nth(newlines - 1)
+                .map_or(rest.len(), |(offset, _)| offset + 1),
+        };
+        let (stripped_line, remainder) = rest.split_at(length);
+        rest = remainder;
+
+        if stripped_line != original {
+            edits.push((
+                line.byte_range(),
+                serde_json::to_string(stripped_line).unwrap(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Applies edits to the source. Edits must be sorted and must not overlap
+fn apply_edits(source_code: &str, edits: Vec<Edit>) -> String {
+    let mut result = String::with_capacity(source_code.len());
+    let mut offset = 0usize;
+    for (range, replacement) in edits {
+        result.push_str(&source_code[offset..range.start]);
+        result.push_str(&replacement);
+        offset = range.end;
+    }
+    result.push_str(&source_code[offset..]);
+
+    result
+}
+
+/// Strips comments from the code cells of a notebook
+///
+/// # Arguments
+/// * `source_code` - The notebook JSON
+/// * `options` - Options controlling what is stripped. If clear_notebook_outputs is set, the
+///   outputs and execution counts of code cells are also cleared
+///
+/// # Return
+/// On success, the notebook JSON with the code cells stripped
+pub fn strip_notebook(source_code: &str, options: &StripOptions) -> Result<String, StrippingError> {
+    let mut parser = TSParser::new();
+    parser.set_language(&tree_sitter_json::LANGUAGE.into())?;
+    let tree = parser.parse(source_code, None).unwrap();
+
+    let root = tree.root_node();
+    if root.has_error() {
+        return Err(StrippingError::InvalidNotebook("invalid JSON".into()));
+    }
+    let notebook = root
+        .named_child(0)
+        .filter(|notebook| notebook.kind() == "object")
+        .ok_or_else(|| StrippingError::InvalidNotebook("expected an object".into()))?;
+    let cells = object_value(&notebook, "cells", source_code)
+        .filter(|cells| cells.kind() == "array")
+        .ok_or_else(|| StrippingError::InvalidNotebook("expected a list of cells".into()))?;
+
+    let language = notebook_language(&notebook, source_code);
+
+    let mut cursor = cells.walk();
+    let code_cells: Vec<Node<'_>> = cells
+        .named_children(&mut cursor)
+        .filter(|cell| nested_string(cell, &["cell_type"], source_code).as_deref() == Some("code"))
+        .collect();
+
+    let mut edits: Vec<Edit> = Vec::new();
+    for cell in code_cells {
+        let cell_source = object_value(&cell, "source", source_code);
+        if let (Some(language), Some(cell_source)) = (language, cell_source) {
+            strip_cell_source(&cell_source, language, source_code, options, &mut edits)?;
+        }
+
+        if options.clear_notebook_outputs {
+            if let Some(outputs) = object_value(&cell, "outputs", source_code) {
+                if outputs.named_child_count() > 0 {
+                    edits.push((outputs.byte_range(), "[]".into()));
+                }
+            }
+            if let Some(execution_count) = object_value(&cell, "execution_count", source_code) {
+                if execution_count.kind() != "null" {
+                    edits.push((execution_count.byte_range(), "null".into()));
+                }
+            }
+        }
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+    Ok(apply_edits(source_code, edits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Heading\n"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 3,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "1 # out\n"
+     ]
+    }
+   ],
+   "source": [
+    "# comment\n",
+    "a = \"é # not\"  # comment\n",
+    "print(a)"
+   ]
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    /// GIVEN A notebook with a Python kernel
+    /// WHEN strip_notebook is called
+    /// THEN comments are removed from code cells only, and the rest of the JSON is unchanged
+    #[test]
+    fn strip_notebook_strips_code_cells() {
+        let result = strip_notebook(NOTEBOOK, &StripOptions::default()).unwrap();
+        let expected = NOTEBOOK
+            .replace(r##""# comment\n","##, r#""\n","#)
+            .replace(r#"# not\"  # comment\n""#, r#"# not\"  \n""#);
+        assert_eq!(result, expected);
+    }
+
+    /// GIVEN A notebook with outputs
+    /// WHEN strip_notebook is called with clear_notebook_outputs
+    /// THEN the outputs and execution counts of code cells are cleared
+    #[test]
+    fn strip_notebook_clears_outputs() {
+        let options = StripOptions {
+            clear_notebook_outputs: true,
+            ..Default::default()
+        };
+        let result = strip_notebook(NOTEBOOK, &options).unwrap();
+
+        let notebook: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(notebook["cells"][1]["outputs"], serde_json::json!([]));
+        assert_eq!(
+            notebook["cells"][1]["execution_count"],
+            serde_json::Value::Null
+        );
+        assert!(result.contains(
+            "   \"execution_count\": null,\n   \"metadata\": {},\n   \"outputs\": [],\n"
+        ));
+    }
+
+    /// GIVEN A file that is not a notebook
+    /// WHEN strip_notebook is called
+    /// THEN an error is returned
+    #[test]
+    fn strip_notebook_rejects_invalid_notebooks() {
+        let result = strip_notebook("[1, 2", &StripOptions::default());
+        assert!(matches!(result, Err(StrippingError::InvalidNotebook(_))));
+
+        let result = strip_notebook("{\"cells\": 1}", &StripOptions::default());
+        assert!(matches!(result, Err(StrippingError::InvalidNotebook(_))));
+    }
+}
@@ -0,0 +1,25 @@
+//! Contains the options that control what is stripped
+//!
+//! By default only comments are stripped. These options allow other content, such as the HTML
+//! comments in Markdown or the outputs of notebooks, to be removed as well
+
+/// Options controlling what is stripped
+#[derive(Debug, Clone, Default)]
+pub struct StripOptions {
+    /// If true, HTML comments are removed from Markdown as well as from fenced code blocks
+    pub markdown_html_comments: bool,
+
+    /// If true, the outputs and execution counts of Jupyter notebook code cells are cleared
+    pub clear_notebook_outputs: bool,
+
+    /// If true, docstrings such as Python module, class and function docstrings are removed, as
+    /// are documentation blocks such as Perl POD, Elixir @doc attributes and GraphQL descriptions
+    pub strip_docstrings: bool,
+
+    /// If true, trailing commas are removed from languages that allow them, such as JSONC,
+    /// so the output is strict JSON
+    pub remove_trailing_commas: bool,
+
+    /// If true, doc comments such as Javadoc, Scaladoc and Rust /// comments are kept
+    pub keep_doc_comments: bool,
+}
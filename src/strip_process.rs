@@ -11,16 +11,20 @@
 //! * load_file. Reads the contents of the file and returns it as a UTF-8 encoded string.
 //! * strip_file. Having identified the language, the comments are removed from the source
 //! * write_file. The stripped file is written back to disk
-use std::{collections::HashSet, fs, path::Path};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use glob::{glob, MatchOptions, Paths};
 use log::{debug, warn};
-use tree_sitter::Parser as TSParser;
 
 use crate::{
     errors::{StrippingError, StrippingResult},
-    languages::{LanguageDefinition, LANGUAGES},
+    languages::{all_languages, find_language, LanguageDefinition, Stripper},
     strip_core::strip_comments,
+    strip_options::StripOptions,
     type_hints::{TypeHint, TypeHints},
 };
 
@@ -69,19 +73,7 @@ fn identify_language_from_hints(
     // Identify the language that corresponds to hint
     let result = matching_hints
         .last()
-        .map(|th| {
-            LANGUAGES
-                .iter()
-                .filter_map(|&language| {
-                    if language.name == th.language {
-                        Some(language)
-                    } else {
-                        None
-                    }
-                })
-                .nth(0)
-        })
-        .flatten();
+        .and_then(|th| find_language(&th.language));
 
     match (result, matching_hints.len()) {
         (Some(language), 1) => {
@@ -144,8 +136,7 @@ fn identify_language_from_filename(
     // Convert the extension to a String, which can fail is someone is deliberately passing bad data
     // We'll also make it lowercase so we can do case insensitive file extension checks
     let path_extension = path_extension
-        .map(|f| f.to_str())
-        .flatten()
+        .and_then(|f| f.to_str())
         .map(|f| f.to_lowercase());
 
     match &path_extension {
@@ -157,17 +148,28 @@ fn identify_language_from_filename(
         }
     }
 
-    // Identify the appropriate language
-    let matching_languages: Vec<&&LanguageDefinition> = LANGUAGES
-        .iter()
-        .filter(|&&language| {
-            return matches!(&path_extension, Some(path_extension) if language.file_extensions.contains(path_extension.as_str())) ||
-                language
-                    .path_globs
-                    .iter()
-                    .any(|path_glob| path_glob.matches_path_with(path, MatchOptions {case_sensitive: false, ..Default::default() }));
+    // Identify the appropriate language. Languages claiming the extension take priority over
+    // those matching a glob, so Makefile.toml is TOML rather than Make
+    let mut matching_languages: Vec<&LanguageDefinition> = all_languages()
+        .filter(|&language| {
+            matches!(&path_extension, Some(path_extension) if language.file_extensions.contains(path_extension.as_str()))
         })
         .collect();
+    if matching_languages.is_empty() {
+        matching_languages = all_languages()
+            .filter(|&language| {
+                language.path_globs.iter().any(|path_glob| {
+                    path_glob.matches_path_with(
+                        path,
+                        MatchOptions {
+                            case_sensitive: false,
+                            ..Default::default()
+                        },
+                    )
+                })
+            })
+            .collect();
+    }
 
     // Ensure only a single item language matches
     let language = match matching_languages.len() {
@@ -187,7 +189,7 @@ fn identify_language_from_filename(
             // error message
             let matching_languages: Vec<&str> = matching_languages
                 .iter()
-                .map(|language| language.name)
+                .map(|language| language.id)
                 .collect();
             let matching_languages = matching_languages.join("/");
             let suggested_pattern = if let Some(path_extension) = &path_extension {
@@ -256,22 +258,19 @@ fn load_file(path: &Path) -> Result<String, StrippingError> {
 /// # Arguments
 /// * `language` - The language to strip as
 /// * `source` - A string containing the source to strip
+/// * `options` - Options controlling what is stripped
 ///
 /// # Return
 /// On success, the source code with all comments removed
-fn strip_file(language: &LanguageDefinition, source: String) -> Result<String, StrippingError> {
-    // Create a parser for the detected language
-    let mut parser = TSParser::new();
-    parser.set_language(&language.language)?;
-
-    // Parse the source
-    let mut tree = parser.parse(source.clone(), None).unwrap();
-
-    // Strip the source
-    Ok(strip_comments(&mut tree, &language, &source))
+fn strip_file(
+    language: &LanguageDefinition,
+    source: String,
+    options: &StripOptions,
+) -> Result<String, StrippingError> {
+    strip_comments(language, &source, options)
 }
 
-/// Writes the stripped source back to disk
+/// Determines the output path for a file, and ensures the parent directory exists
 ///
 /// The file will be written to the output dir at the relative path of path to input_dir.
 /// For example, if input dir is /a/b/c, the output_dir is /g/h/i, and the file is /a/b/c/d/e/f
@@ -281,13 +280,14 @@ fn strip_file(language: &LanguageDefinition, source: String) -> Result<String, S
 /// * `path` - The path the file was originally read from
 /// * `input_dir` - The directory that was searched to find the input file
 /// * `output_dir` - The directory to write to
-/// * `source` - The stripped source
-fn write_file(
+///
+/// # Return
+/// On success, the path to write to
+fn prepare_output_path(
     path: &Path,
     input_dir: &Path,
     output_dir: &Path,
-    source: String,
-) -> Result<(), StrippingError> {
+) -> Result<PathBuf, StrippingError> {
     debug!(
         "{}: Writing to output directory {}",
         path.display(),
@@ -316,10 +316,39 @@ fn write_file(
         fs::create_dir_all(output_path_parent)?;
     }
 
+    Ok(output_path)
+}
+
+/// Writes the stripped source back to disk
+///
+/// # Arguments
+/// * `path` - The path the file was originally read from
+/// * `input_dir` - The directory that was searched to find the input file
+/// * `output_dir` - The directory to write to
+/// * `source` - The stripped source
+fn write_file(
+    path: &Path,
+    input_dir: &Path,
+    output_dir: &Path,
+    source: String,
+) -> Result<(), StrippingError> {
+    let output_path = prepare_output_path(path, input_dir, output_dir)?;
     fs::write(output_path, source)?;
     Ok(())
 }
 
+/// Copies a file to the output directory byte-for-byte
+///
+/// # Arguments
+/// * `path` - The path of the file to copy
+/// * `input_dir` - The directory that was searched to find the input file
+/// * `output_dir` - The directory to write to
+fn copy_file(path: &Path, input_dir: &Path, output_dir: &Path) -> Result<(), StrippingError> {
+    let output_path = prepare_output_path(path, input_dir, output_dir)?;
+    fs::copy(path, output_path)?;
+    Ok(())
+}
+
 /// Finds all jobs in the provided input directory using the glob pattern
 ///
 /// # Arguments
@@ -336,6 +365,16 @@ pub fn find_files(input_dir: &Path, glob_pattern: &str) -> StrippingResult<Paths
     ))?)
 }
 
+/// The outcome of successfully processing a single file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessOutcome {
+    /// Comments were stripped from the file
+    Stripped,
+
+    /// The file was copied unchanged by the null stripper
+    Copied,
+}
+
 /// Performs processing for a single file
 ///
 /// Identifies the language of a file, strips comments, and writes it back to disk
@@ -344,20 +383,34 @@ pub fn find_files(input_dir: &Path, glob_pattern: &str) -> StrippingResult<Paths
 /// * input_dir - The directory to search
 /// * output_dir - The directory to write results to
 /// * type_hints - A type hints structure
+/// * options - Options controlling what is stripped
 /// * path - The path to a single file to process
+///
+/// # Return
+/// On success, whether the file was stripped or copied unchanged
 pub fn process_file(
     input_dir: &Path,
     output_dir: &Path,
     type_hints: &TypeHints,
+    options: &StripOptions,
     path: &Path,
-) -> Result<(), StrippingError> {
+) -> Result<ProcessOutcome, StrippingError> {
     debug!("{}: Processing", path.display());
     let language = identify_language(path, type_hints)?;
+
+    // The null stripper copies the file without loading it, so need not be valid UTF-8
+    if let Stripper::Null = language.stripper {
+        debug!("{}: Copying as {}", path.display(), language.name);
+        copy_file(path, input_dir, output_dir)?;
+        return Ok(ProcessOutcome::Copied);
+    }
+
     let source = load_file(path)?;
 
     debug!("{}: Stripping as {}", path.display(), language.name);
-    let stripped_source = strip_file(language, source)?;
-    write_file(path, input_dir, output_dir, stripped_source)
+    let stripped_source = strip_file(language, source, options)?;
+    write_file(path, input_dir, output_dir, stripped_source)?;
+    Ok(ProcessOutcome::Stripped)
 }
 
 #[cfg(test)]
@@ -365,8 +418,9 @@ mod tests {
     use std::str::FromStr;
 
     use glob::Pattern;
+    use once_cell::sync::Lazy;
 
-    use crate::type_hints::TypeHint;
+    use crate::{languages::LANGUAGES, type_hints::TypeHint};
 
     use super::*;
 
@@ -378,13 +432,7 @@ mod tests {
         let result = identify_language(Path::new("/tmp/test.py"), &vec![]);
         assert!(matches!(
             result,
-            Ok(LanguageDefinition {
-                name: "Python",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
-            })
+            Ok(LanguageDefinition { name: "Python", .. })
         ));
     }
 
@@ -396,13 +444,7 @@ mod tests {
         let result = identify_language(Path::new("/tmp/test.PY"), &vec![]);
         assert!(matches!(
             result,
-            Ok(LanguageDefinition {
-                name: "Python",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
-            })
+            Ok(LanguageDefinition { name: "Python", .. })
         ));
     }
 
@@ -418,10 +460,7 @@ mod tests {
             result,
             Ok(LanguageDefinition {
                 name: "Dockerfile",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
+                ..
             })
         ));
     }
@@ -438,10 +477,7 @@ mod tests {
             result,
             Ok(LanguageDefinition {
                 name: "Dockerfile",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
+                ..
             })
         ));
     }
@@ -452,7 +488,7 @@ mod tests {
     #[test]
     fn identify_language_ambiguity() {
         let result = identify_language(Path::new("/tmp/test.h"), &vec![]);
-        assert!(matches!(result, Err(_)));
+        assert!(result.is_err());
     }
 
     /// GIVEN A path to an unknown file type
@@ -461,7 +497,7 @@ mod tests {
     #[test]
     fn identify_language_unknown() {
         let result = identify_language(Path::new("/tmp/test.bin"), &vec![]);
-        assert!(matches!(result, Err(_)));
+        assert!(result.is_err());
     }
 
     /// GIVEN A path to a known file type
@@ -480,10 +516,7 @@ mod tests {
             result,
             Ok(LanguageDefinition {
                 name: "Javascript",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
+                ..
             })
         ));
     }
@@ -504,10 +537,7 @@ mod tests {
             result,
             Ok(LanguageDefinition {
                 name: "Javascript",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
+                ..
             })
         ));
     }
@@ -528,11 +558,1469 @@ mod tests {
             result,
             Ok(LanguageDefinition {
                 name: "Javascript",
-                file_extensions: _,
-                comment_node_types: _,
-                path_globs: _,
-                language: _
+                ..
+            })
+        ));
+    }
+
+    /// GIVEN A path to a known file type
+    /// AND a type hint naming the language by alias
+    /// WHEN identify_language is called
+    /// THEN the aliased language is returned
+    #[test]
+    fn identify_language_can_use_typehint_aliases() {
+        let type_hints: TypeHints = vec![TypeHint {
+            pattern: Pattern::from_str("**/*.h").unwrap().into(),
+            language: String::from("objc"),
+        }];
+
+        let result = identify_language(Path::new("/tmp/test.h"), &type_hints);
+        assert!(matches!(
+            result,
+            Ok(LanguageDefinition {
+                name: "Objective-C",
+                ..
             })
         ));
     }
+
+    /// GIVEN A path to a known file type
+    /// AND a type hint naming the language with unusual capitalisation
+    /// WHEN identify_language is called
+    /// THEN the language is returned
+    #[test]
+    fn identify_language_typehints_are_case_insensitive() {
+        let type_hints: TypeHints = vec![TypeHint {
+            pattern: Pattern::from_str("**/*.h").unwrap().into(),
+            language: String::from("C++"),
+        }];
+
+        let result = identify_language(Path::new("/tmp/test.h"), &type_hints);
+        assert!(matches!(result, Ok(LanguageDefinition { name: "C++", .. })));
+
+        let type_hints: TypeHints = vec![TypeHint {
+            pattern: Pattern::from_str("**/*.tsx").unwrap().into(),
+            language: String::from("TYPESCRIPT WITH REACT"),
+        }];
+
+        let result = identify_language(Path::new("/tmp/test.tsx"), &type_hints);
+        assert!(matches!(
+            result,
+            Ok(LanguageDefinition {
+                name: "Typescript with React",
+                ..
+            })
+        ));
+    }
+
+    /// GIVEN A path to a nested nginx config file
+    /// WHEN identify_language is called
+    /// THEN the lexer based language is returned
+    #[test]
+    fn identify_language_finds_lexer_languages() {
+        let result = identify_language(Path::new("/tmp/etc/nginx/sites/default.conf"), &vec![]);
+        assert!(matches!(
+            result,
+            Ok(LanguageDefinition {
+                name: "nginx",
+                stripper: Stripper::Lexer(_),
+                ..
+            })
+        ));
+    }
+
+    /// GIVEN A path to a known file type
+    /// AND a type hint selecting the null stripper
+    /// WHEN process_file is called
+    /// THEN the file is copied byte-for-byte
+    #[test]
+    fn process_file_null_stripper_copies_unchanged() {
+        let root = std::env::temp_dir().join("ed_strip_process_file_null_stripper");
+        let input_dir = root.join("input");
+        let output_dir = root.join("output");
+        let path = input_dir.join("data").join("golden.json");
+        // Deliberately not valid UTF-8
+        let contents: &[u8] = b"{ \"a\": 1 } // not a comment\r\n\xff";
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+
+        let type_hints: TypeHints = vec![TypeHint {
+            pattern: Pattern::from_str("**/*.json").unwrap().into(),
+            language: String::from("null"),
+        }];
+
+        let result = process_file(
+            &input_dir,
+            &output_dir,
+            &type_hints,
+            &StripOptions::default(),
+            &path,
+        );
+        assert!(matches!(result, Ok(ProcessOutcome::Copied)));
+        assert_eq!(
+            fs::read(output_dir.join("data").join("golden.json")).unwrap(),
+            contents
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    /// GIVEN HTML containing script and style elements
+    /// WHEN strip_file is called
+    /// THEN comments are removed from the embedded Javascript and CSS, but not other scripts
+    #[test]
+    fn strip_file_strips_embedded_html_languages() {
+        let source = concat!(
+            "<!-- header -->\n",
+            "<script>\n",
+            "  // comment\n",
+            "  var a = \"// not a comment\"; /* two\n  lines */\n",
+            "</script>\n",
+            "<script type=\"text/x-template\"><p>// kept</p></script>\n",
+            "<style>/* comment */ p { color: red; }</style>\n",
+        );
+
+        let result = strip_file(
+            find_language("html").unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "\n",
+                "<script>\n",
+                "  \n",
+                "  var a = \"// not a comment\"; \n",
+                "\n",
+                "</script>\n",
+                "<script type=\"text/x-template\"><p>// kept</p></script>\n",
+                "<style> p { color: red; }</style>\n",
+            )
+        );
+    }
+
+    /// GIVEN A Vue single-file component
+    /// WHEN strip_file is called
+    /// THEN each block is stripped according to its lang attribute, and lines are unchanged
+    #[test]
+    fn strip_file_strips_single_file_components() {
+        let source = concat!(
+            "<template>\n",
+            "  <!-- template comment -->\n",
+            "  <p>{{ message }}</p>\n",
+            "</template>\n",
+            "<script setup lang=\"ts\">\n",
+            "// script comment\n",
+            "const message: string = \"hello\";\n",
+            "</script>\n",
+            "<style lang=\"scss\">\n",
+            "/* style\n comment */\n",
+            "p { color: red; }\n",
+            "</style>\n",
+        );
+
+        let result = strip_file(
+            find_language("vue").unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "<template>\n",
+                "  \n",
+                "  <p>{{ message }}</p>\n",
+                "</template>\n",
+                "<script setup lang=\"ts\">\n",
+                "\n",
+                "const message: string = \"hello\";\n",
+                "</script>\n",
+                "<style lang=\"scss\">\n",
+                "\n",
+                "\n",
+                "p { color: red; }\n",
+                "</style>\n",
+            )
+        );
+    }
+
+    /// GIVEN A PHP template containing HTML, Javascript and CSS
+    /// WHEN strip_file is called
+    /// THEN comments are removed from every layer
+    #[test]
+    fn strip_file_strips_php_templates() {
+        let source = concat!(
+            "<!-- html -->\n",
+            "<div class=\"<?php echo $c; /* php */ ?>\">\n",
+            "<?php // php\n",
+            "$a = 1; # php\n",
+            "?>\n",
+            "<script>\n",
+            "  var a = <?php echo $a; ?>; // js\n",
+            "</script>\n",
+            "<style>/* css */</style>\n",
+        );
+
+        let result = strip_file(
+            find_language("php").unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "\n",
+                "<div class=\"<?php echo $c;  ?>\">\n",
+                "<?php \n",
+                "$a = 1; \n",
+                "?>\n",
+                "<script>\n",
+                "  var a = <?php echo $a; ?>; \n",
+                "</script>\n",
+                "<style></style>\n",
+            )
+        );
+    }
+
+    /// GIVEN A Markdown file containing fenced code blocks and HTML comments
+    /// WHEN strip_file is called
+    /// THEN comments are removed from the code blocks, but the prose is unchanged
+    #[test]
+    fn strip_file_strips_markdown_code_blocks() {
+        let source = concat!(
+            "# Title <!-- inline -->\n",
+            "\n",
+            "<!-- block -->\n",
+            "Prose with # and // markers\n",
+            "\n",
+            "```python\n",
+            "a = 1  # python\n",
+            "```\n",
+            "\n",
+            "> ```js title=\"a.js\"\n",
+            "> /* js\n",
+            "> comment */ let a = 1;\n",
+            "> ```\n",
+            "\n",
+            "```unknown\n",
+            "a = 1  # kept\n",
+            "```\n",
+        );
+
+        let result = strip_file(
+            find_language("md").unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "# Title <!-- inline -->\n",
+                "\n",
+                "<!-- block -->\n",
+                "Prose with # and // markers\n",
+                "\n",
+                "```python\n",
+                "a = 1  \n",
+                "```\n",
+                "\n",
+                "> ```js title=\"a.js\"\n",
+                "> \n",
+                ">  let a = 1;\n",
+                "> ```\n",
+                "\n",
+                "```unknown\n",
+                "a = 1  # kept\n",
+                "```\n",
+            )
+        );
+
+        let options = StripOptions {
+            markdown_html_comments: true,
+            ..Default::default()
+        };
+        let result =
+            strip_file(find_language("md").unwrap(), source.to_string(), &options).unwrap();
+        assert!(result.starts_with("# Title \n\n\nProse with # and // markers\n"));
+    }
+
+    /// GIVEN SQL source containing comments and strings
+    /// WHEN strip_file is called for each dialect
+    /// THEN comments are removed, but strings and executable comments are unchanged
+    #[test]
+    fn strip_file_strips_sql_dialects() {
+        let strip = |language: &str, source: &str| {
+            strip_file(
+                find_language(language).unwrap(),
+                source.to_string(),
+                &StripOptions::default(),
+            )
+            .unwrap()
+        };
+
+        let source = concat!(
+            "/*!40101 SET NAMES utf8 */; -- comment\n",
+            "SELECT '-- not', 'it''s /* not */' /* comment\n",
+            "*/ FROM t;\n",
+        );
+        assert_eq!(
+            strip("sql", source),
+            concat!(
+                "/*!40101 SET NAMES utf8 */; \n",
+                "SELECT '-- not', 'it''s /* not */' \n",
+                " FROM t;\n",
+            )
+        );
+
+        let source = concat!(
+            "/* outer /* inner */ still outer */ SELECT 1;\n",
+            "CREATE FUNCTION f() RETURNS int AS $fn$ -- kept\n",
+            "  SELECT 1; /* kept */\n",
+            "$fn$ LANGUAGE sql; -- comment\n",
+        );
+        assert_eq!(
+            strip("postgres", source),
+            concat!(
+                " SELECT 1;\n",
+                "CREATE FUNCTION f() RETURNS int AS $fn$ -- kept\n",
+                "  SELECT 1; /* kept */\n",
+                "$fn$ LANGUAGE sql; \n",
+            )
+        );
+
+        let source = "SELECT E'it\\'s -- not a comment', e'\\\\' -- comment\n";
+        assert_eq!(
+            strip("postgres", source),
+            "SELECT E'it\\'s -- not a comment', e'\\\\' \n"
+        );
+
+        let source = concat!(
+            "# comment\n",
+            "SELECT /*+ BKA(t) */ 'a\\' # not', 1--1 FROM t; -- comment\n",
+        );
+        assert_eq!(
+            strip("mysql", source),
+            concat!("\n", "SELECT /*+ BKA(t) */ 'a\\' # not', 1--1 FROM t; \n",)
+        );
+
+        // A bare -- at the end of a line or of the file is also a comment
+        assert_eq!(
+            strip("mysql", "SELECT 1;--\r\nSELECT 2;--\nSELECT 3;--"),
+            "SELECT 1;\r\nSELECT 2;\nSELECT 3;"
+        );
+    }
+
+    /// GIVEN A path to a .pb file
+    /// WHEN identify_language is called
+    /// THEN no language is found, as .pb files are usually binary
+    #[test]
+    fn identify_language_ignores_binary_protobuf() {
+        let result = identify_language(Path::new("/tmp/message.pb"), &vec![]);
+        assert!(matches!(
+            result,
+            Err(StrippingError::NoStripperFound { .. })
+        ));
+    }
+
+    /// GIVEN Thrift, FlatBuffers and Cap'n Proto schemas
+    /// WHEN strip_file is called
+    /// THEN comments are removed
+    #[test]
+    fn strip_file_strips_idl_schemas() {
+        let strip = |path: &str, source: &str| {
+            let language = identify_language(Path::new(path), &vec![]).unwrap();
+            strip_file(language, source.to_string(), &StripOptions::default()).unwrap()
+        };
+
+        assert_eq!(
+            strip(
+                "/tmp/a.thrift",
+                "# a\n/** b */\nconst string C = \"#//\" // c\n"
+            ),
+            "\n\nconst string C = \"#//\" \n"
+        );
+        assert_eq!(
+            strip(
+                "/tmp/a.fbs",
+                "/// a\ntable T { s: string = \"//\"; } // b\n"
+            ),
+            "\ntable T { s: string = \"//\"; } \n"
+        );
+        assert_eq!(
+            strip(
+                "/tmp/a.capnp",
+                "@0xdbb9ad1f14bf0b36; # a\nconst c :Text = \"#\";\n"
+            ),
+            "@0xdbb9ad1f14bf0b36; \nconst c :Text = \"#\";\n"
+        );
+    }
+
+    /// GIVEN A batch file with CRLF line endings
+    /// WHEN strip_file is called
+    /// THEN only comment lines are removed, and line endings are unchanged
+    #[test]
+    fn strip_file_strips_batch_files() {
+        let source = concat!(
+            "@echo off\r\n",
+            "REM comment\r\n",
+            "rem\r\n",
+            "@Rem comment\r\n",
+            ":: comment\r\n",
+            "echo REM not a comment\r\n",
+            "set MSG=\"REM not a comment\"\r\n",
+            "if exist a (\r\n",
+            "    rem comment\r\n",
+            "    remark.exe\r\n",
+            ")\r\n",
+        );
+
+        let result = strip_file(
+            find_language("bat").unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "@echo off\r\n",
+                "\r\n",
+                "\r\n",
+                "\r\n",
+                "\r\n",
+                "echo REM not a comment\r\n",
+                "set MSG=\"REM not a comment\"\r\n",
+                "if exist a (\r\n",
+                "    \r\n",
+                "    remark.exe\r\n",
+                ")\r\n",
+            )
+        );
+    }
+
+    /// GIVEN A preprocessed ARM assembly file
+    /// WHEN strip_file is called for each GNU assembler dialect
+    /// THEN comments are removed, but preprocessor directives and immediates are kept
+    #[test]
+    fn strip_file_strips_gnu_assembly() {
+        let source = concat!(
+            "#include \"board.h\"\n",
+            "# define STACK 0x100 /* size */\n",
+            "# comment\n",
+            "\tmov r0, #1 @ comment\n",
+            "\tb main // comment\n",
+        );
+        let strip = |language: &str| {
+            strip_file(
+                find_language(language).unwrap(),
+                source.to_string(),
+                &StripOptions::default(),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            strip("gas"),
+            concat!(
+                "#include \"board.h\"\n",
+                "# define STACK 0x100 \n",
+                "\n",
+                "\tmov r0, #1 @ comment\n",
+                "\tb main \n",
+            )
+        );
+        assert_eq!(
+            strip("gas-arm"),
+            concat!(
+                "#include \"board.h\"\n",
+                "# define STACK 0x100 \n",
+                "\n",
+                "\tmov r0, #1 \n",
+                "\tb main \n",
+            )
+        );
+        assert_eq!(
+            strip("gas-x86"),
+            concat!(
+                "#include \"board.h\"\n",
+                "# define STACK 0x100 \n",
+                "\n",
+                "\tmov r0, \n",
+                "\tb main \n",
+            )
+        );
+    }
+
+    /// GIVEN A NASM file
+    /// WHEN strip_file is called
+    /// THEN comments are removed, but strings containing ; are kept
+    #[test]
+    fn strip_file_strips_nasm() {
+        let result = strip_file(
+            identify_language(Path::new("/tmp/boot.asm"), &vec![]).unwrap(),
+            "%define X 1 ; comment\nmsg db 'a;b', 0 ; comment\n".to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, "%define X 1 \nmsg db 'a;b', 0 \n");
+    }
+
+    /// GIVEN Well known JSON files that allow comments
+    /// WHEN identify_language is called
+    /// THEN they are identified as JSONC
+    #[test]
+    fn identify_language_finds_jsonc_files() {
+        for path in [
+            "/repo/tsconfig.json",
+            "/repo/packages/a/tsconfig.build.json",
+            "/repo/.vscode/settings.json",
+            "/repo/.devcontainer/devcontainer.json",
+        ] {
+            let result = identify_language(Path::new(path), &vec![]);
+            assert!(
+                matches!(result, Ok(LanguageDefinition { id: "jsonc", .. })),
+                "{}",
+                path
+            );
+        }
+    }
+
+    /// GIVEN A JSONC file with comments and trailing commas
+    /// WHEN strip_file is called with and without remove_trailing_commas
+    /// THEN comments are removed, and trailing commas are removed if requested
+    #[test]
+    fn strip_file_strips_jsonc() {
+        let source = concat!(
+            "{\n",
+            "  // comment\n",
+            "  \"url\": \"http://example.com\", /* comment */\n",
+            "  \"paths\": [\"a\", \"b\", /* \"c\" */],\n",
+            "}\n",
+        );
+        let language = find_language("jsonc").unwrap();
+
+        let result = strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "{\n",
+                "  \n",
+                "  \"url\": \"http://example.com\", \n",
+                "  \"paths\": [\"a\", \"b\", ],\n",
+                "}\n",
+            )
+        );
+
+        let options = StripOptions {
+            remove_trailing_commas: true,
+            ..Default::default()
+        };
+        let result = strip_file(language, source.to_string(), &options).unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "{\n",
+                "  \n",
+                "  \"url\": \"http://example.com\", \n",
+                "  \"paths\": [\"a\", \"b\" ]\n",
+                "}\n",
+            )
+        );
+        let _: serde_json::Value = serde_json::from_str(&result).unwrap();
+    }
+
+    /// GIVEN A Visual Studio solution
+    /// WHEN strip_file is called
+    /// THEN comments are removed, but the header and version lines are kept
+    #[test]
+    fn strip_file_strips_solutions() {
+        let source = concat!(
+            "\u{feff}\r\n",
+            "Microsoft Visual Studio Solution File, Format Version 12.00\r\n",
+            "# Visual Studio Version 17\r\n",
+            "VisualStudioVersion = 17.0.31903.59\r\n",
+            "# comment\r\n",
+            "Project(\"{FAE04EC0-301F-11D3-BF4B-00C04F79EFBC}\") = \"A#B\", \"A#B.csproj\", \"{1}\"\r\n",
+            "EndProject\r\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/a.sln"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, source.replace("# comment", ""));
+    }
+
+    /// GIVEN A module-definition file
+    /// WHEN strip_file is called
+    /// THEN comments are removed, but quoted strings are kept
+    #[test]
+    fn strip_file_strips_module_definitions() {
+        let result = strip_file(
+            identify_language(Path::new("/tmp/a.def"), &vec![]).unwrap(),
+            "; comment\nLIBRARY \"a;b\" ; comment\nEXPORTS\n  f @1\n".to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, "\nLIBRARY \"a;b\" \nEXPORTS\n  f @1\n");
+    }
+
+    /// GIVEN systemd units and drop-in files
+    /// WHEN identify_language is called
+    /// THEN they are identified as systemd units
+    #[test]
+    fn identify_language_finds_systemd_units() {
+        for path in [
+            "/repo/deploy/app.service",
+            "/repo/deploy/app.timer",
+            "/etc/systemd/system/app.service.d/override.conf",
+        ] {
+            let result = identify_language(Path::new(path), &vec![]);
+            assert!(
+                matches!(result, Ok(LanguageDefinition { id: "systemd", .. })),
+                "{}",
+                path
+            );
+        }
+    }
+
+    /// GIVEN systemd units and desktop entries with # and ; in values
+    /// WHEN strip_file is called
+    /// THEN only full line comments are removed
+    #[test]
+    fn strip_file_strips_full_line_comments_only() {
+        let strip = |path: &str, source: &str| {
+            let language = identify_language(Path::new(path), &vec![]).unwrap();
+            strip_file(language, source.to_string(), &StripOptions::default()).unwrap()
+        };
+
+        assert_eq!(
+            strip(
+                "/tmp/a.service",
+                "# comment\n[Service]\n  ; comment\nExecStart=/bin/sh -c 'a # b; c' ; d\n"
+            ),
+            "\n[Service]\n  \nExecStart=/bin/sh -c 'a # b; c' ; d\n"
+        );
+        assert_eq!(
+            strip(
+                "/tmp/a.desktop",
+                "# comment\n[Desktop Entry]\nName=C# Editor\nCategories=Development;IDE;\n"
+            ),
+            "\n[Desktop Entry]\nName=C# Editor\nCategories=Development;IDE;\n"
+        );
+    }
+
+    /// GIVEN Gradle, Jenkins and sbt build files
+    /// WHEN identify_language is called
+    /// THEN they are identified as Groovy or Scala
+    #[test]
+    fn identify_language_finds_jvm_build_files() {
+        for (path, id) in [
+            ("/repo/build.gradle", "groovy"),
+            ("/repo/app/settings.gradle", "groovy"),
+            ("/repo/Jenkinsfile", "groovy"),
+            ("/repo/ci/Jenkinsfile.release", "groovy"),
+            ("/repo/scripts/release.groovy", "groovy"),
+            ("/repo/build.sbt", "scala"),
+            ("/repo/project/plugins.sbt", "scala"),
+            ("/repo/src/main/scala/App.scala", "scala"),
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, id, "{}", path);
+        }
+    }
+
+    /// GIVEN Java source with Javadoc, ordinary comments, an empty comment and a banner
+    /// WHEN strip_file is called with and without keep_doc_comments
+    /// THEN Javadoc comments are only kept if requested, and /**/ and /*** are not Javadoc
+    #[test]
+    fn strip_file_keeps_doc_comments() {
+        let source = concat!(
+            "/** Class docs */\n",
+            "/**/\n",
+            "/*** banner ***/\n",
+            "class A {\n",
+            "    /* comment */\n",
+            "    /**\n",
+            "     * Method docs\n",
+            "     */\n",
+            "    void f() {} // comment\n",
+            "}\n",
+        );
+        let language = find_language("java").unwrap();
+
+        let result = strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+        assert_eq!(
+            result,
+            "\n\n\nclass A {\n    \n    \n\n\n    void f() {} \n}\n"
+        );
+
+        let options = StripOptions {
+            keep_doc_comments: true,
+            ..Default::default()
+        };
+        let result = strip_file(language, source.to_string(), &options).unwrap();
+        assert_eq!(
+            result,
+            source
+                .replace("/**/", "")
+                .replace("/*** banner ***/", "")
+                .replace("/* comment */", "")
+                .replace(" // comment", " ")
+        );
+    }
+
+    /// GIVEN Perl, R and R Markdown files
+    /// WHEN identify_language is called
+    /// THEN they are identified by extension, regardless of case
+    #[test]
+    fn identify_language_finds_perl_and_r() {
+        for (path, id) in [
+            ("/repo/bin/tool.pl", "perl"),
+            ("/repo/lib/Tool/Util.pm", "perl"),
+            ("/repo/t/basic.t", "perl"),
+            ("/repo/analysis/model.R", "r"),
+            ("/repo/analysis/helpers.r", "r"),
+            ("/repo/reports/summary.Rmd", "markdown"),
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, id, "{}", path);
+        }
+    }
+
+    /// GIVEN An R Markdown document with chunk options in the info strings
+    /// WHEN strip_file is called
+    /// THEN comments are removed from the chunks of known languages
+    #[test]
+    fn strip_file_strips_r_markdown_chunks() {
+        let source = concat!(
+            "# Report\n",
+            "\n",
+            "```{python setup, echo=FALSE}\n",
+            "a = 1  # python\n",
+            "```\n",
+            "\n",
+            "```{unknown}\n",
+            "a = 1  # kept\n",
+            "```\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/report.Rmd"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, source.replace("  # python", "  "));
+    }
+
+    /// GIVEN Verilog, SystemVerilog and VHDL sources containing synthesis directives
+    /// WHEN strip_file is called
+    /// THEN ordinary comments are removed, but directives and attributes are kept, and VHDL
+    ///      directives are matched in any case
+    #[test]
+    fn strip_file_keeps_hdl_directives() {
+        let strip = |path: &str, source: &str| {
+            let language = identify_language(Path::new(path), &vec![]).unwrap();
+            strip_file(language, source.to_string(), &StripOptions::default()).unwrap()
+        };
+
+        let verilog = concat!(
+            "// comment\n",
+            "(* keep = \"true\" *) reg a; /* comment */\n",
+            "always @(*) $display(\"// not a comment\");\n",
+            "// synopsys translate_off\n",
+            "initial $finish; //synthesis translate_on\n",
+            "/* verilator lint_off UNUSED */\n",
+        );
+        let expected = concat!(
+            "\n",
+            "(* keep = \"true\" *) reg a; \n",
+            "always @(*) $display(\"// not a comment\");\n",
+            "// synopsys translate_off\n",
+            "initial $finish; //synthesis translate_on\n",
+            "/* verilator lint_off UNUSED */\n",
+        );
+        assert_eq!(strip("/tmp/a.v", verilog), expected);
+        assert_eq!(strip("/tmp/a.sv", verilog), expected);
+        assert_eq!(strip("/tmp/a.svh", verilog), expected);
+
+        let vhdl = concat!(
+            "-- comment\n",
+            "constant Q : character := '\"'; -- comment\n",
+            "constant S : string := \"a\"\"--b\\\"; -- comment\n",
+            "-- pragma translate_off\n",
+            "signal x : bit; --synthesis translate_on\n",
+            "-- Synopsys translate_off\n",
+            "-- PRAGMA synthesis_off\n",
+            "/* Xilinx translate_on */ -- Synopsis\n",
+        );
+        let expected = concat!(
+            "\n",
+            "constant Q : character := '\"'; \n",
+            "constant S : string := \"a\"\"--b\\\"; \n",
+            "-- pragma translate_off\n",
+            "signal x : bit; --synthesis translate_on\n",
+            "-- Synopsys translate_off\n",
+            "-- PRAGMA synthesis_off\n",
+            "/* Xilinx translate_on */ \n",
+        );
+        assert_eq!(strip("/tmp/a.vhd", vhdl), expected);
+        assert_eq!(strip("/tmp/a.VHDL", vhdl), expected);
+    }
+
+    /// GIVEN Nix files in nested directories
+    /// WHEN identify_language is called
+    /// THEN they are identified as Nix
+    #[test]
+    fn identify_language_finds_nix_files() {
+        for path in [
+            "/repo/flake.nix",
+            "/repo/nix/default.nix",
+            "/repo/services/api/shell.nix",
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, "nix", "{}", path);
+        }
+    }
+
+    /// GIVEN Nix source with comment markers inside ${} interpolations, escaped ${ and URIs
+    /// WHEN strip_file is called
+    /// THEN only the comments are removed, including comments inside interpolations
+    #[test]
+    fn strip_file_strips_nix_interpolations() {
+        let source = concat!(
+            "{ pkgs ? import <nixpkgs> {} }: # comment\n",
+            "let\n",
+            "  a = \"${\"#\"} /* not */\"; # comment\n",
+            "  b = \"${ /* comment */ pkgs.hello }/bin # not\";\n",
+            "  c = ''\n",
+            "    $${HOME} ''${x} # not ''' /* not\n",
+            "    ${ { d = \"}\"; }.d } # not */\n",
+            "  ''; # comment\n",
+            "  e = \"$${x} # not \\\" # not\";\n",
+            "  f = { url = https://example.com/a#b; }; # comment\n",
+            "in a\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/flake.nix"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "{ pkgs ? import <nixpkgs> {} }: \n",
+                "let\n",
+                "  a = \"${\"#\"} /* not */\"; \n",
+                "  b = \"${  pkgs.hello }/bin # not\";\n",
+                "  c = ''\n",
+                "    $${HOME} ''${x} # not ''' /* not\n",
+                "    ${ { d = \"}\"; }.d } # not */\n",
+                "  ''; \n",
+                "  e = \"$${x} # not \\\" # not\";\n",
+                "  f = { url = https://example.com/a#b; }; \n",
+                "in a\n",
+            )
+        );
+    }
+
+    /// GIVEN Dart source with interpolations, raw strings and nested block comments
+    /// WHEN strip_file is called with and without keep_doc_comments
+    /// THEN comments are removed, and /// comments are only kept if requested
+    #[test]
+    fn strip_file_strips_dart() {
+        let source = concat!(
+            "/// Doc\n",
+            "var a = '${b['#']} // not'; // comment\n",
+            "var c = r'\\' // comment\n",
+            "/* outer /* inner */ still */ var d = \"\"\"\n",
+            "// not ${e /* comment */}\n",
+            "\"\"\";\n",
+        );
+        let language = identify_language(Path::new("/tmp/main.dart"), &vec![]).unwrap();
+
+        let result = strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "\n",
+                "var a = '${b['#']} // not'; \n",
+                "var c = r'\\' \n",
+                " var d = \"\"\"\n",
+                "// not ${e }\n",
+                "\"\"\";\n",
+            )
+        );
+
+        let options = StripOptions {
+            keep_doc_comments: true,
+            ..Default::default()
+        };
+        let result = strip_file(language, source.to_string(), &options).unwrap();
+        assert!(result.starts_with("/// Doc\nvar a"));
+    }
+
+    /// GIVEN Zig source with multiline strings and character literals
+    /// WHEN strip_file is called
+    /// THEN comments are removed, but the strings are unchanged
+    #[test]
+    fn strip_file_strips_zig() {
+        let result = strip_file(
+            identify_language(Path::new("/tmp/main.zig"), &vec![]).unwrap(),
+            concat!(
+                "//! Module\n",
+                "const s =\n",
+                "    \\\\ // not a comment\n",
+                "; // comment\n",
+                "const c = '\"'; // comment\n",
+            )
+            .to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "\n",
+                "const s =\n",
+                "    \\\\ // not a comment\n",
+                "; \n",
+                "const c = '\"'; \n",
+            )
+        );
+    }
+
+    /// GIVEN A Zig file with doc comments and //// separator comments
+    /// WHEN strip_file is called with keep_doc_comments
+    /// THEN the doc comments are kept, but the separators are removed
+    #[test]
+    fn strip_file_keeps_zig_doc_comments() {
+        let options = StripOptions {
+            keep_doc_comments: true,
+            ..Default::default()
+        };
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/main.zig"), &vec![]).unwrap(),
+            concat!(
+                "//! Module\n",
+                "//////////\n",
+                "/// Function\n",
+                "fn f() void {} // comment\n",
+                "////\n",
+            )
+            .to_string(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "//! Module\n",
+                "\n",
+                "/// Function\n",
+                "fn f() void {} \n",
+                "\n"
+            )
+        );
+    }
+
+    /// GIVEN Terraform, Terragrunt and Packer files
+    /// WHEN identify_language is called
+    /// THEN they are identified as HCL
+    #[test]
+    fn identify_language_finds_hcl_files() {
+        for path in [
+            "/repo/infra/main.tf",
+            "/repo/infra/prod.tfvars",
+            "/repo/infra/live/prod/terragrunt.hcl",
+            "/repo/images/base.pkr.hcl",
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, "hcl", "{}", path);
+        }
+    }
+
+    /// GIVEN A GraphQL schema with descriptions and comments
+    /// WHEN strip_file is called with and without strip_docstrings
+    /// THEN the comments are removed, and the descriptions are only removed with strip_docstrings,
+    ///      while string values in lists and arguments are kept
+    #[test]
+    fn strip_file_keeps_graphql_descriptions() {
+        let source = concat!(
+            "# comment\n",
+            "\"\"\"\n",
+            "A user # not a comment \\\"\"\" still a description\n",
+            "\"\"\"\n",
+            "type User { # comment\n",
+            "  \"Display name # not a comment\"\n",
+            "  name(format: String = \"#\"): String\n",
+            "  tags(\n",
+            "    \"Tags to match\"\n",
+            "    # comment\n",
+            "    match: [String] = [\n",
+            "      \"a\",\n",
+            "      \"b\"\n",
+            "    ]\n",
+            "  ): [String] @deprecated(\n",
+            "    reason:\n",
+            "      \"Use name\"\n",
+            "  )\n",
+            "}\n",
+        );
+
+        let language = identify_language(Path::new("/tmp/schema.graphql"), &vec![]).unwrap();
+        let stripped = source
+            .replace("# comment\n\"\"\"", "\n\"\"\"")
+            .replace("{ # comment", "{ ")
+            .replace("    # comment\n", "    \n");
+
+        let result = strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+        assert_eq!(result, stripped);
+
+        let options = StripOptions {
+            strip_docstrings: true,
+            ..Default::default()
+        };
+        let result = strip_file(language, source.to_string(), &options).unwrap();
+        assert_eq!(
+            result,
+            stripped
+                .replace(
+                    "\"\"\"\nA user # not a comment \\\"\"\" still a description\n\"\"\"",
+                    "\n\n"
+                )
+                .replace("\"Display name # not a comment\"", "")
+                .replace("\"Tags to match\"", "")
+        );
+    }
+
+    /// GIVEN Haskell, Elixir and Erlang sources and build files
+    /// WHEN identify_language is called
+    /// THEN they are identified by extension or filename
+    #[test]
+    fn identify_language_finds_beam_and_haskell_files() {
+        for (path, id) in [
+            ("/repo/src/Main.hs", "haskell"),
+            ("/repo/lib/app/server.ex", "elixir"),
+            ("/repo/mix.exs", "elixir"),
+            ("/repo/apps/api/src/api.erl", "erlang"),
+            ("/repo/apps/api/include/api.hrl", "erlang"),
+            ("/repo/apps/api/src/api.app.src", "erlang"),
+            ("/repo/rebar.config", "erlang"),
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, id, "{}", path);
+        }
+    }
+
+    /// GIVEN Erlang source with % inside strings, atoms and character literals
+    /// WHEN strip_file is called
+    /// THEN only the comments are removed
+    #[test]
+    fn strip_file_strips_erlang() {
+        let source = concat!(
+            "%% @doc Module docs\n",
+            "-module(a). % comment\n",
+            "f() -> [$%, $\", $\\\", \"100%\", '%atom'], % comment\n",
+            "    io:format(\"~p~n\", [$\\%]). % comment\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/a.erl"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "\n",
+                "-module(a). \n",
+                "f() -> [$%, $\", $\\\", \"100%\", '%atom'], \n",
+                "    io:format(\"~p~n\", [$\\%]). \n",
+            )
+        );
+    }
+
+    /// GIVEN Extensionless and prefix or suffix named build files in nested directories
+    /// WHEN identify_language is called
+    /// THEN they are identified as Make or Dockerfile
+    #[test]
+    fn identify_language_finds_build_files() {
+        for (path, id) in [
+            ("/repo/Makefile", "make"),
+            ("/repo/sub/Makefile", "make"),
+            ("/repo/sub/makefile", "make"),
+            ("/repo/sub/GNUmakefile", "make"),
+            ("/repo/sub/Makefile.in", "make"),
+            ("/repo/sub/rules.mk", "make"),
+            ("/repo/sub/rules.make", "make"),
+            ("/repo/Dockerfile", "dockerfile"),
+            ("/repo/sub/Dockerfile", "dockerfile"),
+            ("/repo/sub/Dockerfile.dev", "dockerfile"),
+            ("/repo/sub/app.Dockerfile", "dockerfile"),
+            ("/repo/sub/Containerfile", "dockerfile"),
+            ("/repo/sub/Containerfile.prod", "dockerfile"),
+            ("/repo/sub/app.containerfile", "dockerfile"),
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, id, "{}", path);
+        }
+    }
+
+    /// GIVEN Files matching a build file glob such as Makefile.*, with an extension claimed by
+    ///       another language
+    /// WHEN identify_language is called
+    /// THEN they are identified by their extension
+    #[test]
+    fn identify_language_prefers_extensions_to_globs() {
+        for (path, id) in [
+            ("/repo/Makefile.toml", "toml"),
+            ("/repo/sub/Dockerfile.yaml", "yaml"),
+            ("/repo/sub/Jenkinsfile.groovy", "groovy"),
+            ("/repo/sub/Jenkinsfile.release", "groovy"),
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, id, "{}", path);
+        }
+    }
+
+    /// GIVEN Every path glob of the built-in languages
+    /// WHEN identify_language is called with a nested path matching the glob
+    /// THEN only the language owning the glob is identified
+    #[test]
+    fn identify_language_matches_globs_in_nested_directories() {
+        for language in LANGUAGES.iter().map(|&language| Lazy::force(language)) {
+            for path_glob in &language.path_globs {
+                assert!(
+                    path_glob.as_str().starts_with("**/"),
+                    "{} does not match nested paths",
+                    path_glob
+                );
+
+                let path = format!(
+                    "/repo/a/b/{}",
+                    path_glob.as_str()[3..]
+                        .replace("**", "c/d")
+                        .replace('*', "e")
+                );
+                let result = identify_language(Path::new(&path), &vec![]);
+                assert!(
+                    matches!(result, Ok(result) if result.id == language.id),
+                    "{} did not identify {}",
+                    path,
+                    language.id
+                );
+            }
+        }
+    }
+
+    /// GIVEN CMake modules, toolchain files, package config templates and presets
+    /// WHEN identify_language is called
+    /// THEN they are identified as CMake, or as Null for the presets, which are copied unchanged
+    #[test]
+    fn identify_language_finds_cmake_files() {
+        for (path, id) in [
+            ("/repo/CMakeLists.txt", "cmake"),
+            ("/repo/src/lib/CMakeLists.txt", "cmake"),
+            ("/repo/cmake/FindFoo.cmake", "cmake"),
+            ("/repo/cmake/toolchains/arm-none-eabi.cmake", "cmake"),
+            ("/repo/cmake/FooConfig.cmake.in", "cmake"),
+            ("/repo/CMakePresets.json", "null"),
+            ("/repo/CMakeUserPresets.json", "null"),
+        ] {
+            let result = identify_language(Path::new(path), &vec![]).unwrap();
+            assert_eq!(result.id, id, "{}", path);
+        }
+    }
+
+    /// GIVEN A language whose comments separate the tokens around them
+    /// WHEN strip_file is called
+    /// THEN comments between tokens are replaced with a space, and other comments are removed
+    #[test]
+    fn strip_file_keeps_tokens_separated() {
+        let language = LanguageDefinition {
+            name: "C with separating comments",
+            id: "c-separated",
+            aliases: [].into(),
+            file_extensions: [].into(),
+            path_globs: vec![],
+            doc_comment_prefixes: &[],
+            stripper: Stripper::TreeSitter {
+                language: tree_sitter_c::LANGUAGE.into(),
+                comment_node_types: ["comment"].into(),
+                separator_comment_types: ["comment"].into(),
+                docstrings: None,
+                injections: vec![],
+            },
+        };
+        let leaked: &'static LanguageDefinition = Box::leak(Box::new(language));
+
+        let result = strip_file(
+            leaked,
+            "int/**/x = 1;/* a */\n/* b */ int y; // c\n".to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, "int x = 1;\n int y; \n");
+    }
+
+    /// GIVEN Real-world CMake containing bracket comments, bracket arguments and quoted arguments
+    /// WHEN strip_file is called
+    /// THEN comments are removed, and argument lists keep the same arguments
+    #[test]
+    fn strip_file_strips_cmake() {
+        let source = concat!(
+            "#[==[\n",
+            "Licensed under the MIT license ]] still a comment\n",
+            "#]==]\n",
+            "cmake_minimum_required(VERSION 3.21) # comment\n",
+            "project(Foo LANGUAGES CXX)\n",
+            "set(SOURCES a.cpp#[[comment]]b.cpp)\n",
+            "message(STATUS \"# not a comment\" [=[ # not a comment ]=])\n",
+            "target_link_libraries(foo\n",
+            "  PRIVATE bar # comment\n",
+            "  #[[ comment ]] baz)\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/cmake/Foo.cmake"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "\n",
+                "\n",
+                "\n",
+                "cmake_minimum_required(VERSION 3.21) \n",
+                "project(Foo LANGUAGES CXX)\n",
+                "set(SOURCES a.cpp b.cpp)\n",
+                "message(STATUS \"# not a comment\" [=[ # not a comment ]=])\n",
+                "target_link_libraries(foo\n",
+                "  PRIVATE bar \n",
+                "   baz)\n",
+            )
+        );
+    }
+
+    /// GIVEN Groovy and Scala sources with doc comments, ordinary comments and strings
+    /// WHEN strip_file is called with and without keep_doc_comments
+    /// THEN comments are removed, and Groovydoc and Scaladoc are only kept if requested
+    #[test]
+    fn strip_file_strips_groovy_and_scala() {
+        let sources = [
+            (
+                "/tmp/build.gradle",
+                concat!(
+                    "/** Groovydoc */\n",
+                    "def greet(name) { // comment\n",
+                    "    /* comment */ println \"Hello // ${name} /* not */\"\n",
+                    "}\n",
+                ),
+            ),
+            (
+                "/tmp/Main.scala",
+                concat!(
+                    "/** Scaladoc */\n",
+                    "object Main { // comment\n",
+                    "  /* comment */ val s = \"Hello // /* not */\"\n",
+                    "}\n",
+                ),
+            ),
+        ];
+
+        for (path, source) in sources {
+            let language = identify_language(Path::new(path), &vec![]).unwrap();
+            let stripped = source
+                .replace(" // comment", " ")
+                .replace("/* comment */ ", " ");
+
+            let result =
+                strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+            let (_, without_docs) = stripped.split_once('\n').unwrap();
+            assert_eq!(result, format!("\n{}", without_docs), "{}", path);
+
+            let options = StripOptions {
+                keep_doc_comments: true,
+                ..Default::default()
+            };
+            let result = strip_file(language, source.to_string(), &options).unwrap();
+            assert_eq!(result, stripped, "{}", path);
+        }
+    }
+
+    /// GIVEN A Perl script with POD, a here document and an __END__ section
+    /// WHEN strip_file is called with and without strip_docstrings
+    /// THEN comments are removed, POD is only removed with strip_docstrings, and the here
+    ///      document and the data after __END__ are kept
+    #[test]
+    fn strip_file_strips_perl() {
+        let source = concat!(
+            "use strict; # comment\n",
+            "my $last = $#array << 1;\n",
+            "my $s = \"not # a comment\";\n",
+            "print <<~EOF, 'x # y';\n",
+            "    # not a comment\n",
+            "    EOF\n",
+            "\n",
+            "=head1 NAME\n",
+            "\n",
+            "Example # not a comment\n",
+            "\n",
+            "=cut\n",
+            "\n",
+            "print $s; # comment\n",
+            "__END__\n",
+            "# data\n",
+        );
+        let language = identify_language(Path::new("/tmp/script.pl"), &vec![]).unwrap();
+        let stripped = source.replace(" # comment", " ");
+
+        let result = strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+        assert_eq!(result, stripped);
+
+        let options = StripOptions {
+            strip_docstrings: true,
+            ..Default::default()
+        };
+        let result = strip_file(language, source.to_string(), &options).unwrap();
+        assert_eq!(
+            result,
+            stripped.replace("=head1 NAME\n\nExample # not a comment\n\n=cut", "\n\n\n\n")
+        );
+    }
+
+    /// GIVEN Perl with comment markers and quotes inside quote-like operators and regular
+    ///       expressions, and variables and hash keys named like operators
+    /// WHEN strip_file is called
+    /// THEN the operators and regular expressions are kept, and only the comments are removed
+    #[test]
+    fn strip_file_keeps_perl_quote_like_operators() {
+        let source = concat!(
+            "$x =~ s/#.*//; # comment\n",
+            "my @w = qw(a#b c); # comment\n",
+            "if ($x =~ m/\"/) { # comment\n",
+            "    print \"-- # not a comment\"; # comment\n",
+            "}\n",
+            "$x =~ s{#}\n",
+            "       {x}g; # comment\n",
+            "$x =~ tr/#//d; y/a/#/; # comment\n",
+            "my $q = q<#<nested>#> . qq{#{a}} . qr!#!; # comment\n",
+            "print 1 if $x !~ /#'/ && grep(/#/, @a); # comment\n",
+            "my @f = split /#/, $line; # comment\n",
+            "my $y = $s / 2 + $h{s} / $q; # comment\n",
+            "my %h = (s => 1, y => 2); # comment\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/script.pl"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result, source.replace(" # comment", " "));
+    }
+
+    /// GIVEN A Perl module whose POD follows __DATA__
+    /// WHEN strip_file is called with strip_docstrings
+    /// THEN everything after __DATA__ is kept unchanged
+    #[test]
+    fn strip_file_keeps_perl_data_section() {
+        let source = concat!(
+            "print while <DATA>; # comment\n",
+            "__DATA__\n",
+            "# data\n",
+            "=pod\n",
+            "=cut\n",
+        );
+        let options = StripOptions {
+            strip_docstrings: true,
+            ..Default::default()
+        };
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/Module.pm"), &vec![]).unwrap(),
+            source.to_string(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(result, source.replace(" # comment", " "));
+    }
+
+    /// GIVEN An R script with roxygen documentation comments
+    /// WHEN strip_file is called with and without keep_doc_comments
+    /// THEN roxygen comments are only kept with keep_doc_comments
+    #[test]
+    fn strip_file_strips_r_roxygen_comments() {
+        let source = concat!(
+            "#' Add one\n",
+            "#' @param x A number\n",
+            "add_one <- function(x) { # comment\n",
+            "  x + 1  # comment\n",
+            "}\n",
+        );
+        let language = identify_language(Path::new("/tmp/add.R"), &vec![]).unwrap();
+        let stripped = source.replace(" # comment", " ");
+
+        let result = strip_file(language, source.to_string(), &StripOptions::default()).unwrap();
+        assert_eq!(
+            result,
+            stripped.replace("#' Add one\n#' @param x A number\n", "\n\n")
+        );
+
+        let options = StripOptions {
+            keep_doc_comments: true,
+            ..Default::default()
+        };
+        let result = strip_file(language, source.to_string(), &options).unwrap();
+        assert_eq!(result, stripped);
+    }
+    /// GIVEN A Haskell module with pragmas, Haddock comments, and ordinary and nested comments
+    /// GIVEN A Haskell module with pragmas, Haddock comments and ordinary comments
+    /// THEN the comments are removed whole, including nested ones, but the pragmas are kept
+    /// THEN the comments are removed, but the pragmas are kept
+    #[test]
+    fn strip_file_keeps_haskell_pragmas() {
+        let source = concat!(
+            "{-# LANGUAGE OverloadedStrings #-}\n",
+            "{-# OPTIONS_GHC -Wall #-}\n",
+            "-- | Module\n",
+            "module Main where\n",
+            "\n",
+            "{- comment -}\n",
+            "{- outer {- inner -} still outer -}\n",
+            "main :: IO ()\n",
+            "main = putStrLn \"-- not a comment\" -- comment\n",
+            "{-# INLINE main #-}\n",
+        );
+
+        let result = strip_file(
+            identify_language(Path::new("/tmp/Main.hs"), &vec![]).unwrap(),
+            source.to_string(),
+            &StripOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            source
+                .replace("-- | Module\n", "\n")
+                .replace("{- comment -}\n", "\n")
+                .replace("{- outer {- inner -} still outer -}\n", "\n")
+                .replace(" -- comment\n", " \n")
+        );
+    }
 }
@@ -1,4 +1,4 @@
-///! Contains structures related to the type hints mechanism
+//! Contains structures related to the type hints mechanism
 use std::{ops::Deref, path::Path, str::FromStr};
 
 use glob::Pattern;
@@ -44,7 +44,7 @@ impl Deref for GlobPattern {
 pub struct TypeHint {
     /// A glob pattern that matches file(s)
     pub pattern: GlobPattern,
-    /// A language name to use
+    /// A language name, identifier or alias to use. This is case insensitive
     pub language: String,
 }
 